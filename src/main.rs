@@ -1,4 +1,8 @@
 use eframe::{egui, App, CreationContext, Frame, NativeOptions};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 fn main() -> eframe::Result<()> {
     let options = NativeOptions {
@@ -49,471 +53,7977 @@ enum AppScreen {
     Studio,
 }
 
-const SPLASH_WINDOW_SIZE: f32 = 200.0;
-const LOGIN_WINDOW_DEFAULT_SIZE: [f32; 2] = [1200.0, 760.0];
-const LOGIN_WINDOW_MIN_SIZE: [f32; 2] = [960.0, 620.0];
-const STUDIO_WINDOW_SIZE: [f32; 2] = [1600.0, 920.0];
-
-#[derive(Debug, Error)]
-enum AppIoError {
-    #[error("Serialization failed: {0}")]
-    Serialization(#[from] serde_json::Error),
-    #[error("Filesystem error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("Archive error: {0}")]
-    Zip(#[from] zip::result::ZipError),
+/// Project-replacing actions held back until unsaved changes are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiscardAction {
+    NewProject,
+    LoadProject,
+    CloseWindow,
+    LogOut,
+    ImportProjectJson,
+    NewFromPreset,
 }
 
-impl ToolView {
+impl DiscardAction {
     fn label(self) -> &'static str {
         match self {
-            ToolView::ProjectSettings => "Project Settings",
-            ToolView::HoursEstimator => "Hours Estimator",
-            ToolView::DrawingsOverlay => "Drawings Overlay",
-            ToolView::Templates => "Template Tool",
-            ToolView::FeatureControlCenter => "Feature Control Center",
+            DiscardAction::NewProject => "starting a new project",
+            DiscardAction::LoadProject => "loading another project",
+            DiscardAction::CloseWindow => "closing AutoMate",
+            DiscardAction::LogOut => "logging out",
+            DiscardAction::ImportProjectJson => "importing a project",
+            DiscardAction::NewFromPreset => "starting a project from a preset",
         }
     }
 }
 
+/// One proposed equipment tag rename from a retag dry run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TagChange {
+    object_id: u64,
+    name: String,
+    old_tag: String,
+    new_tag: String,
+}
+
+/// How a template's graphics or commissioning hours are derived.
 #[derive(
-    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, EnumIter, JsonSchema,
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, JsonSchema,
 )]
-enum ObjectType {
-    Building,
-    Controller,
-    Equipment,
-    Point,
+enum HourDriver {
+    /// The template's flat per-equipment hours.
+    #[default]
+    FlatHours,
+    /// Unit count (graphic pages or commissioning tests) times the estimator rate.
+    PerUnit,
 }
 
-impl Tab {
+impl HourDriver {
     fn label(self) -> &'static str {
         match self {
-            Self::LineAudit => "Line Audit",
-            Self::Refactor => "Refactor Plan",
-            Self::Features => "Feature Lab",
-            Self::Dependencies => "Dependency Strategy",
+            HourDriver::FlatHours => "Flat hours",
+            HourDriver::PerUnit => "Per unit",
         }
     }
 }
 
-#[derive(Clone)]
-struct LineReview {
-    area: &'static str,
-    finding: &'static str,
-    action: &'static str,
-    keep: bool,
+/// Review state of a placed overlay token, matching the overlay legend.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, EnumIter, JsonSchema,
+)]
+enum OverlayStatus {
+    #[default]
+    Specified,
+    Assumed,
+    NeedsClarification,
 }
 
-#[derive(Clone)]
-struct FeatureIdea {
-    name: &'static str,
-    value: &'static str,
-    complexity: &'static str,
+impl OverlayStatus {
+    fn label(self) -> &'static str {
+        match self {
+            OverlayStatus::Specified => "Specified",
+            OverlayStatus::Assumed => "Assumed",
+            OverlayStatus::NeedsClarification => "Needs Clarification",
+        }
+    }
+
+    fn color(self) -> Color32 {
+        match self {
+            OverlayStatus::Specified => Color32::from_rgb(64, 196, 120),
+            OverlayStatus::Assumed => Color32::from_rgb(240, 180, 60),
+            OverlayStatus::NeedsClarification => Color32::from_rgb(230, 80, 80),
+        }
+    }
 }
 
-#[derive(Clone)]
-struct DependencyDecision {
-    crate_name: &'static str,
-    decision: &'static str,
-    rationale: &'static str,
+/// Overlay element under the cursor when a context menu was opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverlayTarget {
+    Node(usize),
+    Line(usize),
 }
 
-struct AutoMateApp {
-    tab: Tab,
-    dark_mode: bool,
-    ui_density: f32,
-    search: String,
-    reviews: Vec<LineReview>,
-    refactor_steps: Vec<&'static str>,
-    features: Vec<FeatureIdea>,
-    dependencies: Vec<DependencyDecision>,
+/// Screen-space radius used to hit-test tokens and wires on the overlay.
+const OVERLAY_HIT_RADIUS: f32 = 12.0;
+
+/// Real-world scale captured by the overlay calibration tool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+struct DrawingScale {
+    units_per_pixel: f32,
+    unit: String,
 }
 
-impl AutoMateApp {
-    fn new(cc: &CreationContext<'_>) -> Self {
-        cc.egui_ctx.set_visuals(egui::Visuals::dark());
+const POINT_KINDS: [PointKind; 5] = [
+    PointKind::AI,
+    PointKind::DI,
+    PointKind::AO,
+    PointKind::DO,
+    PointKind::Network,
+];
+
+/// Per-`PointKind` multipliers on point-based hours. Missing fields load as 1.0.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct PointKindWeights {
+    ai: f32,
+    di: f32,
+    ao: f32,
+    #[serde(rename = "do")]
+    do_: f32,
+    network: f32,
+}
+
+impl Default for PointKindWeights {
+    fn default() -> Self {
         Self {
-            tab: Tab::LineAudit,
-            dark_mode: true,
-            ui_density: 1.0,
-            search: String::new(),
-            reviews: seed_reviews(),
-            refactor_steps: vec![
-                "Split monolithic model into core domain structs + dedicated services",
-                "Extract estimating engine into testable pure module",
-                "Replace scattered state mutations with command-style actions",
-                "Consolidate export flow behind unified proposal pipeline",
-                "Normalize naming and remove duplicate data fields",
-                "Introduce view-model layer between UI and domain",
-                "Add project diagnostics panel with deterministic checks",
-                "Capture performance metrics for large drawings",
-            ],
-            features: seed_features(),
-            dependencies: seed_dependencies(),
+            ai: 1.0,
+            di: 1.0,
+            ao: 1.0,
+            do_: 1.0,
+            network: 1.0,
         }
     }
+}
 
-    fn top_bar(&mut self, ctx: &egui::Context) {
-        egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
-            ui.add_space(4.0);
-            ui.horizontal_wrapped(|ui| {
-                ui.heading("🚀 AutoMate BAS Studio 2.0");
-                ui.separator();
-                ui.label("Reconstruction workspace");
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui
-                        .selectable_label(self.dark_mode, "Dark")
-                        .on_hover_text("Toggle theme")
-                        .clicked()
-                    {
-                        self.dark_mode = !self.dark_mode;
-                        if self.dark_mode {
-                            ctx.set_visuals(egui::Visuals::dark());
-                        } else {
-                            ctx.set_visuals(egui::Visuals::light());
-                        }
-                    }
-                    ui.add(egui::Slider::new(&mut self.ui_density, 0.8..=1.4).text("Density"));
-                });
-            });
-            ui.add_space(4.0);
-        });
+impl PointKindWeights {
+    fn weight(&self, kind: PointKind) -> f32 {
+        match kind {
+            PointKind::AI => self.ai,
+            PointKind::DI => self.di,
+            PointKind::AO => self.ao,
+            PointKind::DO => self.do_,
+            PointKind::Network => self.network,
+        }
     }
 
-    fn side_nav(&mut self, ctx: &egui::Context) {
-        egui::SidePanel::left("left_nav")
-            .resizable(true)
-            .default_width(240.0)
-            .show(ctx, |ui| {
-                ui.heading("Workspace");
-                ui.separator();
-                for tab in [Tab::LineAudit, Tab::Refactor, Tab::Features, Tab::Dependencies] {
-                    let selected = self.tab == tab;
-                    if ui.selectable_label(selected, tab.label()).clicked() {
-                        self.tab = tab;
-                    }
-                }
-                ui.separator();
-                ui.label("Global Search");
-                ui.text_edit_singleline(&mut self.search);
-                ui.add_space(8.0);
-                let completion = (self.refactor_steps.len() as f32 / 10.0).min(1.0);
-                ui.add(egui::ProgressBar::new(completion).text("2.0 Plan Progress"));
-                ui.small("Target: complete 10 strategic changes");
-            });
+    fn weight_mut(&mut self, kind: PointKind) -> &mut f32 {
+        match kind {
+            PointKind::AI => &mut self.ai,
+            PointKind::DI => &mut self.di,
+            PointKind::AO => &mut self.ao,
+            PointKind::DO => &mut self.do_,
+            PointKind::Network => &mut self.network,
+        }
     }
 
-    fn central_ui(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.spacing_mut().item_spacing = egui::vec2(10.0 * self.ui_density, 8.0 * self.ui_density);
-            match self.tab {
-                Tab::LineAudit => self.render_line_audit(ui),
-                Tab::Refactor => self.render_refactor(ui),
-                Tab::Features => self.render_features(ui),
-                Tab::Dependencies => self.render_dependencies(ui),
-            }
-        });
+    /// Effective point count for hour math: each point counts as its kind's weight.
+    fn weighted_count<'a>(&self, points: impl IntoIterator<Item = &'a BasObject>) -> f32 {
+        points
+            .into_iter()
+            .filter(|o| o.object_type == ObjectType::Point)
+            .map(|o| self.weight(o.point_kind))
+            .sum()
     }
+}
 
-    fn render_line_audit(&self, ui: &mut egui::Ui) {
-        ui.heading("main.rs line-by-line audit summary");
-        ui.label("Each entry captures what should be kept, improved, or removed.");
-        ui.separator();
+/// Hourly billing rates. Category rates fall back to `blended` when unset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct LaborRates {
+    blended: f32,
+    engineering: Option<f32>,
+    graphics: Option<f32>,
+    commissioning: Option<f32>,
+}
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for entry in self.filtered_reviews() {
-                ui.group(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.strong(entry.area);
-                        let chip = if entry.keep { "KEEP" } else { "CHANGE" };
-                        let color = if entry.keep {
-                            egui::Color32::from_rgb(40, 180, 110)
-                        } else {
-                            egui::Color32::from_rgb(225, 135, 65)
-                        };
-                        ui.colored_label(color, chip);
-                    });
-                    ui.label(format!("Finding: {}", entry.finding));
-                    ui.label(format!("Action: {}", entry.action));
-                });
-            }
-        });
+impl Default for LaborRates {
+    fn default() -> Self {
+        Self {
+            blended: 125.0,
+            engineering: None,
+            graphics: None,
+            commissioning: None,
+        }
     }
+}
 
-    fn filtered_reviews(&self) -> Vec<&LineReview> {
-        self.reviews
-            .iter()
-            .filter(|r| {
-                self.search.is_empty()
-                    || r.area.to_lowercase().contains(&self.search.to_lowercase())
-                    || r.finding.to_lowercase().contains(&self.search.to_lowercase())
-                    || r.action.to_lowercase().contains(&self.search.to_lowercase())
-            })
-            .collect()
+impl LaborRates {
+    fn engineering(&self) -> f32 {
+        self.engineering.unwrap_or(self.blended)
     }
 
-    fn render_refactor(&self, ui: &mut egui::Ui) {
-        ui.heading("Reconstruction blueprint");
-        ui.label("A practical sequence to evolve main.rs into a maintainable platform.");
-        ui.separator();
+    fn graphics(&self) -> f32 {
+        self.graphics.unwrap_or(self.blended)
+    }
 
-        for (idx, step) in self.refactor_steps.iter().enumerate() {
-            ui.horizontal(|ui| {
-                ui.monospace(format!("{:02}", idx + 1));
-                ui.label(*step);
-            });
-        }
+    fn commissioning(&self) -> f32 {
+        self.commissioning.unwrap_or(self.blended)
     }
+}
 
-    fn render_features(&self, ui: &mut egui::Ui) {
-        ui.heading("10 new features for 2.0");
-        ui.separator();
+/// Result of a background page render, tagged with the request that produced it.
+struct OverlayRender {
+    generation: u64,
+    page_count: usize,
+    /// Texture pixels per page-space pixel actually used, after the size cap.
+    scale: f32,
+    image: Result<egui::ColorImage, String>,
+}
 
-        egui::Grid::new("features_grid")
-            .striped(true)
-            .num_columns(3)
-            .show(ui, |ui| {
-                ui.strong("Feature");
-                ui.strong("User Value");
-                ui.strong("Complexity");
-                ui.end_row();
+/// Longest texture side a drawing render may produce, whatever the render scale.
+const OVERLAY_MAX_TEXTURE_SIDE: f32 = 8192.0;
+const PDF_RENDER_SCALES: [f32; 3] = [1.0, 2.0, 3.0];
 
-                for feature in &self.features {
-                    ui.label(feature.name);
-                    ui.label(feature.value);
-                    ui.label(feature.complexity);
-                    ui.end_row();
-                }
-            });
+/// Current `Project::schema_version`. Bump it together with a new `PROJECT_MIGRATIONS` step.
+const PROJECT_SCHEMA_VERSION: u32 = 2;
+
+/// `PROJECT_MIGRATIONS[n]` upgrades a project from schema `n` to `n + 1`.
+const PROJECT_MIGRATIONS: [fn(&mut Project); PROJECT_SCHEMA_VERSION as usize] =
+    [migrate_v0_template_points, migrate_v1_proposal_clauses];
+
+/// An autosave left behind by a previous session, offered back on startup.
+#[derive(Debug, Clone)]
+struct RecoveryCandidate {
+    path: PathBuf,
+    project_uuid: Option<uuid::Uuid>,
+    modified: std::time::SystemTime,
+}
+
+/// Workstation preferences that follow the user rather than the project file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+struct AppPreferences {
+    ui_scale: f32,
+    accent_color: [u8; 3],
+    autosave_minutes: u32,
+    /// Timestamped autosave copies kept per project in `autosave_dir`; 0 keeps none.
+    autosave_generations: u32,
+    show_overlay_grid: bool,
+    /// Last operator ID that signed in. The passphrase is never stored.
+    last_operator_id: String,
+    /// Controller models offered in `right_properties`; empty means the built-in list.
+    controller_types: Vec<String>,
+    /// License SKUs offered in `right_properties`; empty means the built-in list.
+    controller_licenses: Vec<String>,
+    /// Drawing texture resolution relative to the page's point width.
+    pdf_render_scale: f32,
+    /// Equipment makes and models entered so far, offered as autocomplete.
+    known_makes: BTreeSet<String>,
+    known_models: BTreeSet<String>,
+    overlay_grid_step: f32,
+    /// Unmultiplied RGBA.
+    overlay_grid_color: [u8; 4],
+    /// Per-controller point count that `controller_loading_issues` always flags; 0
+    /// leaves only the relative check.
+    controller_point_threshold: u32,
+    /// Reusable proposal clauses offered by `proposal_clause_fields`.
+    standard_assumptions: Vec<String>,
+    standard_exclusions: Vec<String>,
+    /// Draw PDF form fields and annotations. Off by default: most drawing sets have
+    /// none, and some consultant sets carry stale form appearances that garble the sheet.
+    render_pdf_form_data: bool,
+    /// Where each project was last saved, keyed by `project_uuid`. Recovery skips
+    /// autosaves that are older than that file.
+    saved_project_paths: BTreeMap<uuid::Uuid, PathBuf>,
+    /// Set once name-based template archives have been carried over to the `archived`
+    /// flag, so a template named "... archive" can be restored later.
+    archived_templates_migrated: bool,
+}
+
+impl Default for AppPreferences {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            accent_color: [64, 140, 255],
+            autosave_minutes: 5,
+            autosave_generations: 3,
+            show_overlay_grid: true,
+            last_operator_id: String::new(),
+            controller_types: Vec::new(),
+            controller_licenses: Vec::new(),
+            pdf_render_scale: 1.0,
+            known_makes: BTreeSet::new(),
+            known_models: BTreeSet::new(),
+            overlay_grid_step: DEFAULT_OVERLAY_GRID_STEP,
+            overlay_grid_color: DEFAULT_OVERLAY_GRID_COLOR,
+            standard_assumptions: Vec::new(),
+            standard_exclusions: Vec::new(),
+            render_pdf_form_data: false,
+            controller_point_threshold: 0,
+            saved_project_paths: BTreeMap::new(),
+            archived_templates_migrated: false,
+        }
     }
+}
 
-    fn render_dependencies(&self, ui: &mut egui::Ui) {
-        ui.heading("Cargo dependency decisions");
-        ui.label("Curated keep/remove/add strategy for a faster and cleaner build graph.");
-        ui.separator();
+const DEFAULT_CONTROLLER_TYPES: [&str; 2] = ["Lynxspring Edge", "JENEsys"];
+const DEFAULT_CONTROLLER_LICENSES: [&str; 5] = ["Edge 10", "Edge 25", "Edge 50", "Edge 100", "Supervisor"];
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            for dep in &self.dependencies {
-                ui.group(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.strong(dep.crate_name);
-                        ui.label("→");
-                        ui.colored_label(egui::Color32::LIGHT_BLUE, dep.decision);
-                    });
-                    ui.label(dep.rationale);
-                });
-            }
-        });
+impl AppPreferences {
+    /// Company logo as uploaded; kept as its own file beside the settings.
+    fn logo_path() -> PathBuf {
+        Self::path().with_file_name("company_logo")
     }
 
-    fn feature_control_center_view(&mut self, ui: &mut Ui) {
-        ui.heading("Feature Control Center");
-        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
-        ui.add_space(8.0);
+    fn controller_type_options(&self) -> Vec<String> {
+        list_or_defaults(&self.controller_types, &DEFAULT_CONTROLLER_TYPES)
+    }
 
-        let (ready_count, ready_total) = self.export_readiness_score();
-        let overlay_ready = self.project.overlay_pdf.is_some();
-        let template_ready = !self.user_templates.is_empty();
-        let has_equipment = self
-            .project
-            .objects
+    fn controller_license_options(&self) -> Vec<String> {
+        list_or_defaults(&self.controller_licenses, &DEFAULT_CONTROLLER_LICENSES)
+    }
+    /// Stored beside the user template library.
+    fn path() -> PathBuf {
+        templates_store_path().with_file_name("app_settings.json")
+    }
+
+    fn load() -> Self {
+        std::fs::read(Self::path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), AppIoError> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Standard controller build-out: type, license, and the equipment templates stamped
+/// beneath it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+struct ControllerTemplate {
+    name: String,
+    controller_type: String,
+    controller_license: String,
+    equipment: Vec<String>,
+}
+
+/// The user template library at `templates_store_path`: equipment and controller
+/// templates in one file. Template exports use the same shape.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UserTemplateStore {
+    equipment: Vec<EquipmentTemplate>,
+    controllers: Vec<ControllerTemplate>,
+}
+
+impl UserTemplateStore {
+    /// Also accepts the older bare array of equipment templates.
+    fn from_slice(bytes: &[u8]) -> serde_json::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Legacy(Vec<EquipmentTemplate>),
+            Store(UserTemplateStore),
+        }
+        Ok(match serde_json::from_slice(bytes)? {
+            Stored::Legacy(equipment) => Self {
+                equipment,
+                controllers: Vec::new(),
+            },
+            Stored::Store(store) => store,
+        })
+    }
+
+    fn load() -> Self {
+        std::fs::read(templates_store_path())
+            .ok()
+            .and_then(|bytes| Self::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn write(&self, path: &std::path::Path) -> Result<(), AppIoError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+const UNCATEGORIZED_TEMPLATES: &str = "Uncategorized";
+
+/// Local operator credentials. Only an argon2 PHC hash of the passphrase is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LocalCredentials {
+    operator_id: String,
+    password_hash: String,
+}
+
+impl LocalCredentials {
+    /// Stored beside the user template library, like `AppPreferences`.
+    fn path() -> PathBuf {
+        templates_store_path().with_file_name("credentials.json")
+    }
+
+    fn load() -> Option<Self> {
+        std::fs::read(Self::path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    }
+
+    fn create(operator_id: &str, passphrase: &str) -> Result<Self, AppIoError> {
+        use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = argon2::Argon2::default()
+            .hash_password(passphrase.as_bytes(), &salt)?
+            .to_string();
+        Ok(Self {
+            operator_id: operator_id.trim().to_string(),
+            password_hash,
+        })
+    }
+
+    fn save(&self) -> Result<(), AppIoError> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn verify(&self, operator_id: &str, passphrase: &str) -> bool {
+        use argon2::password_hash::{PasswordHash, PasswordVerifier};
+        if !self.operator_id.eq_ignore_ascii_case(operator_id.trim()) {
+            return false;
+        }
+        PasswordHash::new(&self.password_hash)
+            .map(|hash| {
+                argon2::Argon2::default()
+                    .verify_password(passphrase.as_bytes(), &hash)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Canvas size used by the overlay when no drawing is loaded.
+const OVERLAY_DEFAULT_CANVAS: egui::Vec2 = egui::vec2(1200.0, 1600.0);
+const OVERLAY_ZOOM_MIN: f32 = 0.1;
+const OVERLAY_ZOOM_MAX: f32 = 8.0;
+
+/// Default page-space spacing of the overlay grid; also the snap step.
+const DEFAULT_OVERLAY_GRID_STEP: f32 = 36.0;
+const DEFAULT_OVERLAY_GRID_COLOR: [u8; 4] = [255, 255, 255, 18];
+
+/// Undo depth for tree edits, matching the overlay history cap.
+const PROJECT_HISTORY_LIMIT: usize = 50;
+
+/// Tokens and wires of the active sheet, captured before an overlay edit.
+#[derive(Debug, Clone)]
+struct OverlaySnapshot {
+    nodes: Vec<OverlayNode>,
+    lines: Vec<OverlayLine>,
+}
+
+/// Tree state captured before a structural edit.
+#[derive(Debug, Clone)]
+struct ProjectSnapshot {
+    objects: Vec<BasObject>,
+    next_id: u64,
+    selected_object: Option<u64>,
+}
+
+/// Saved estimator baseline stored in `Project::snapshots`. `totals` follows the
+/// `estimate_hours` order: engineering, graphics, commissioning, custom, overhead, total.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+struct EstimateSnapshot {
+    label: String,
+    taken_at: String,
+    estimator: EstimatorSettings,
+    totals: [f32; 6],
+}
+
+const ESTIMATE_COLUMNS: [&str; 6] = ["Eng", "Gfx", "Cx", "Custom", "Overhead", "Total"];
+
+/// Eng/graphics/commissioning hours rolled up to one building or controller.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct HoursRollup {
+    id: u64,
+    name: String,
+    engineering: f32,
+    graphics: f32,
+    commissioning: f32,
+}
+
+impl HoursRollup {
+    fn total(&self) -> f32 {
+        self.engineering + self.graphics + self.commissioning
+    }
+}
+
+/// One equipment's share of the estimate, or the points wired straight to one
+/// controller, after the project factors.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct EstimateRow {
+    object_id: u64,
+    building_id: Option<u64>,
+    controller_id: Option<u64>,
+    /// Engineering, graphics, commissioning.
+    hours: [f32; 3],
+}
+
+/// `estimate_hours` output: the per-object rows and the project totals they sum to,
+/// in `ESTIMATE_COLUMNS` order.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct HoursEstimate {
+    rows: Vec<EstimateRow>,
+    totals: [f32; 6],
+    /// Factored engineering hours contributed by each point kind, in `POINT_KINDS` order.
+    kind_hours: [f32; 5],
+}
+
+/// How an Hours Adjustments slider acts on `estimate_hours`, so its marginal readout
+/// can be worked out from one estimate instead of re-running it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FactorEffect {
+    /// Scales every row's hours (complexity, renovation, integration).
+    RowMultiplier,
+    /// Adds this percentage of the subtotal as QA/PM/risk overhead.
+    OverheadPercent,
+    /// Weights one point kind's engineering hours.
+    PointWeight(PointKind),
+}
+
+impl FactorEffect {
+    fn neutral(self) -> f32 {
+        match self {
+            FactorEffect::OverheadPercent => 0.0,
+            FactorEffect::RowMultiplier | FactorEffect::PointWeight(_) => 1.0,
+        }
+    }
+}
+
+/// Travel, per-diem and other non-labor cost on the proposal. Kept out of hour math.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct ExpenseLine {
+    description: String,
+    quantity: f32,
+    unit_cost: f32,
+}
+
+impl Default for ExpenseLine {
+    fn default() -> Self {
+        Self {
+            description: String::new(),
+            quantity: 1.0,
+            unit_cost: 0.0,
+        }
+    }
+}
+
+impl ExpenseLine {
+    fn total(&self) -> f32 {
+        self.quantity * self.unit_cost
+    }
+}
+
+/// Sort column for the equipment points table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PointsSort {
+    Name,
+    Kind,
+}
+
+/// How "Retag equipment" builds tags: type prefix, separator, zero-padded sequence.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct TagScheme {
+    separator: String,
+    padding: usize,
+}
+
+impl Default for TagScheme {
+    fn default() -> Self {
+        Self {
+            separator: "-".to_string(),
+            padding: 3,
+        }
+    }
+}
+
+impl TagScheme {
+    fn prefix_for(&self, equipment_type: &str) -> String {
+        let prefix: String = equipment_type
+            .trim()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_uppercase();
+        if prefix.is_empty() {
+            "EQ".to_string()
+        } else {
+            prefix
+        }
+    }
+
+    fn format(&self, prefix: &str, sequence: u32) -> String {
+        format!("{prefix}{}{sequence:0width$}", self.separator, width = self.padding)
+    }
+
+    /// Sequence number of `tag` if it follows this scheme for `prefix`.
+    fn sequence_of(&self, prefix: &str, tag: &str) -> Option<u32> {
+        tag.trim()
+            .strip_prefix(prefix)?
+            .strip_prefix(self.separator.as_str())?
+            .parse()
+            .ok()
+    }
+}
+
+/// State of the "Add N…" equipment dialog.
+#[derive(Debug, Clone)]
+struct BulkEquipmentRequest {
+    controller_id: u64,
+    count: u32,
+    template_name: String,
+    base_name: String,
+}
+
+/// One finding from "Validate Project". Fixes are offered, never applied automatically.
+#[derive(Debug, Clone, PartialEq)]
+enum ValidationIssue {
+    MissingParent { object_id: u64 },
+    WrongParentType { object_id: u64 },
+    NoTemplate { object_id: u64 },
+    DanglingOverlayNode { sheet: usize, node_idx: usize, object_id: u64 },
+}
+
+/// One sheet of the project's drawing set. The active sheet's content is mirrored into
+/// the `Project::overlay_*` fields that the overlay tools edit; `stash_active_drawing`
+/// copies it back before saving or switching.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct DrawingSheet {
+    name: String,
+    /// Sanitized source file name of the PDF, if one is attached.
+    pdf_file: Option<String>,
+    overlay_nodes: Vec<OverlayNode>,
+    overlay_lines: Vec<OverlayLine>,
+    drawing_scale: Option<DrawingScale>,
+    /// Zoom and pan last used on this sheet; `None` opens at 100% from the origin.
+    view: Option<OverlayView>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+struct OverlayView {
+    zoom: f32,
+    pan: [f32; 2],
+}
+
+/// Files embedded in a `.m8` bundle that can be written back out on their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EmbeddedAsset {
+    OverviewImage,
+    DrawingPdf,
+}
+
+/// Object-type toggles above the tree. View state only; combined with the text search
+/// when deciding which rows to show.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TreeTypeFilter {
+    buildings: bool,
+    controllers: bool,
+    equipment: bool,
+    points: bool,
+    /// Narrows the Points toggle to one kind.
+    point_kind: Option<PointKind>,
+}
+
+impl Default for TreeTypeFilter {
+    fn default() -> Self {
+        Self {
+            buildings: true,
+            controllers: true,
+            equipment: true,
+            points: true,
+            point_kind: None,
+        }
+    }
+}
+
+impl TreeTypeFilter {
+    /// Shows a single object type.
+    fn only(kind: ObjectType) -> Self {
+        Self {
+            buildings: kind == ObjectType::Building,
+            controllers: kind == ObjectType::Controller,
+            equipment: kind == ObjectType::Equipment,
+            points: kind == ObjectType::Point,
+            point_kind: None,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    fn matches(&self, obj: &BasObject) -> bool {
+        match obj.object_type {
+            ObjectType::Building => self.buildings,
+            ObjectType::Controller => self.controllers,
+            ObjectType::Equipment => self.equipment,
+            ObjectType::Point => self.points && self.point_kind.map_or(true, |kind| obj.point_kind == kind),
+        }
+    }
+}
+
+/// Clipboard payload for "Copy Subtree": the root first, ids renumbered from 1 and the
+/// root detached so the JSON doesn't leak ids from the source project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SubtreeClipboard {
+    format: String,
+    objects: Vec<BasObject>,
+}
+
+const SUBTREE_CLIPBOARD_FORMAT: &str = "automate-subtree/1";
+
+/// Disk reads done on a worker while the splash is up.
+struct StartupPreload {
+    user_templates: Vec<EquipmentTemplate>,
+    controller_templates: Vec<ControllerTemplate>,
+    recovery_candidates: Vec<RecoveryCandidate>,
+}
+
+impl StartupPreload {
+    fn spawn(saved_project_paths: BTreeMap<uuid::Uuid, PathBuf>) -> mpsc::Receiver<Self> {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let store = UserTemplateStore::load();
+            let _ = tx.send(Self {
+                user_templates: store.equipment,
+                controller_templates: store.controllers,
+                recovery_candidates: find_recovery_candidates(&saved_project_paths),
+            });
+        });
+        rx
+    }
+}
+
+/// Whether the proposal export writes one combined document or one per building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProposalExportMode {
+    Combined,
+    PerBuilding,
+}
+
+/// Align/distribute operations over the selected overlay tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverlayArrange {
+    AlignLeft,
+    AlignRight,
+    AlignTop,
+    AlignBottom,
+    DistributeHorizontally,
+    DistributeVertically,
+}
+
+impl OverlayArrange {
+    const ALL: [Self; 6] = [
+        Self::AlignLeft,
+        Self::AlignRight,
+        Self::AlignTop,
+        Self::AlignBottom,
+        Self::DistributeHorizontally,
+        Self::DistributeVertically,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::AlignLeft => "⇤ Align left",
+            Self::AlignRight => "⇥ Align right",
+            Self::AlignTop => "⤒ Align top",
+            Self::AlignBottom => "⤓ Align bottom",
+            Self::DistributeHorizontally => "↔ Distribute horizontally",
+            Self::DistributeVertically => "↕ Distribute vertically",
+        }
+    }
+
+    /// Distributing needs two fixed ends and something between them.
+    fn min_selection(self) -> usize {
+        match self {
+            Self::DistributeHorizontally | Self::DistributeVertically => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// A shop's standard estimating basis, saved as `<name>.json` in `presets_dir` and used
+/// to seed new projects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProjectPreset {
+    name: String,
+    estimator: EstimatorSettings,
+    custom_hour_lines: Vec<CustomHourLine>,
+    assumptions: Vec<String>,
+    exclusions: Vec<String>,
+    /// Full copies, so a preset still works on a workstation without these templates.
+    templates: Vec<EquipmentTemplate>,
+}
+
+impl ProjectPreset {
+    /// Beside the equipment template store.
+    fn presets_dir() -> PathBuf {
+        templates_store_path().with_file_name("presets")
+    }
+}
+
+const SPLASH_WINDOW_SIZE: f32 = 200.0;
+/// The splash stays up at least this long so it doesn't flash, and moves on by itself
+/// once startup loading is done. Any key or click skips it after the minimum.
+const SPLASH_MIN_DURATION: Duration = Duration::from_millis(600);
+const SPLASH_MAX_DURATION: Duration = Duration::from_millis(2200);
+const LOGIN_WINDOW_DEFAULT_SIZE: [f32; 2] = [1200.0, 760.0];
+const LOGIN_WINDOW_MIN_SIZE: [f32; 2] = [960.0, 620.0];
+const STUDIO_WINDOW_SIZE: [f32; 2] = [1600.0, 920.0];
+
+#[derive(Debug, Error)]
+enum AppIoError {
+    #[error("Serialization failed: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Filesystem error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Archive error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Credential error: {0}")]
+    Credentials(#[from] argon2::password_hash::Error),
+}
+
+impl ToolView {
+    fn label(self) -> &'static str {
+        match self {
+            ToolView::ProjectSettings => "Project Settings",
+            ToolView::HoursEstimator => "Hours Estimator",
+            ToolView::DrawingsOverlay => "Drawings Overlay",
+            ToolView::Templates => "Template Tool",
+            ToolView::FeatureControlCenter => "Feature Control Center",
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, EnumIter, JsonSchema,
+)]
+enum ObjectType {
+    Building,
+    Controller,
+    Equipment,
+    Point,
+}
+
+/// Markup and sales tax applied after the labor/material subtotal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(default)]
+struct CommercialTerms {
+    markup_percent: f32,
+    tax_percent: f32,
+    material_subtotal: f32,
+    labor_taxable: bool,
+    materials_taxable: bool,
+}
+
+impl Default for CommercialTerms {
+    fn default() -> Self {
+        Self {
+            markup_percent: 0.0,
+            tax_percent: 0.0,
+            material_subtotal: 0.0,
+            labor_taxable: false,
+            materials_taxable: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CostSummary {
+    labor: f32,
+    materials: f32,
+    markup: f32,
+    tax: f32,
+}
+
+impl CostSummary {
+    fn new(labor: f32, terms: &CommercialTerms) -> Self {
+        let materials = terms.material_subtotal.max(0.0);
+        let markup_factor = terms.markup_percent.max(0.0) / 100.0;
+        let markup = (labor + materials) * markup_factor;
+        // Markup follows the line it was applied to, so taxable lines carry their share.
+        let mut taxable = 0.0;
+        if terms.labor_taxable {
+            taxable += labor * (1.0 + markup_factor);
+        }
+        if terms.materials_taxable {
+            taxable += materials * (1.0 + markup_factor);
+        }
+        let tax = taxable * terms.tax_percent.max(0.0) / 100.0;
+        Self {
+            labor,
+            materials,
+            markup,
+            tax,
+        }
+    }
+
+    fn subtotal(&self) -> f32 {
+        self.labor + self.materials
+    }
+
+    fn total(&self) -> f32 {
+        self.subtotal() + self.markup + self.tax
+    }
+}
+
+impl Tab {
+    fn label(self) -> &'static str {
+        match self {
+            Self::LineAudit => "Line Audit",
+            Self::Refactor => "Refactor Plan",
+            Self::Features => "Feature Lab",
+            Self::Dependencies => "Dependency Strategy",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct LineReview {
+    area: &'static str,
+    finding: &'static str,
+    action: &'static str,
+    keep: bool,
+}
+
+#[derive(Clone)]
+struct FeatureIdea {
+    name: &'static str,
+    value: &'static str,
+    complexity: &'static str,
+}
+
+#[derive(Clone)]
+struct DependencyDecision {
+    crate_name: &'static str,
+    decision: &'static str,
+    rationale: &'static str,
+}
+
+struct AutoMateApp {
+    tab: Tab,
+    dark_mode: bool,
+    ui_density: f32,
+    search: String,
+    reviews: Vec<LineReview>,
+    refactor_steps: Vec<&'static str>,
+    features: Vec<FeatureIdea>,
+    dependencies: Vec<DependencyDecision>,
+    pending_template_reset: Option<u64>,
+    overlay_search: String,
+    overlay_search_cursor: usize,
+    overlay_highlight: Option<usize>,
+    dirty: bool,
+    pending_discard: Option<DiscardAction>,
+    close_confirmed: bool,
+    retag_skip_manual: bool,
+    retag_preview: Option<Vec<TagChange>>,
+    overlay_context_target: Option<OverlayTarget>,
+    overlay_drag_node: Option<usize>,
+    overlay_selection: BTreeSet<usize>,
+    overlay_band_start: Option<egui::Pos2>,
+    overlay_bulk_type: ObjectType,
+    overlay_page: usize,
+    overlay_page_count: usize,
+    calibration_active: bool,
+    calibration_points: Vec<[f32; 2]>,
+    calibration_distance: f32,
+    calibration_unit: String,
+    selected_objects: HashSet<u64>,
+    tree_anchor: Option<u64>,
+    bulk_move_target: Option<u64>,
+    autosave_rx: Option<mpsc::Receiver<Result<PathBuf, String>>>,
+    overlay_render_rx: Option<mpsc::Receiver<OverlayRender>>,
+    overlay_render_generation: u64,
+    recovery_candidates: Vec<RecoveryCandidate>,
+    preferences: AppPreferences,
+    pending_template_import: Option<Vec<EquipmentTemplate>>,
+    template_export_selection: HashSet<String>,
+    controller_templates: Vec<ControllerTemplate>,
+    template_search: String,
+    credentials: Option<LocalCredentials>,
+    login_password_confirm: String,
+    overlay_snap_to_grid: bool,
+    project_undo: Vec<ProjectSnapshot>,
+    project_redo: Vec<ProjectSnapshot>,
+    snapshot_label: String,
+    points_table_sort: PointsSort,
+    points_table_ascending: bool,
+    tree_focused: bool,
+    collapse_before_search: Option<HashSet<u64>>,
+    bulk_equipment: Option<BulkEquipmentRequest>,
+    validation_report: Option<Vec<ValidationIssue>>,
+    drawing_pdf_bytes: Vec<Option<Vec<u8>>>,
+    overlay_texture_scale: f32,
+    company_logo_bytes: Option<Vec<u8>>,
+    company_logo_texture: Option<egui::TextureHandle>,
+    wire_snap_to_nodes: bool,
+    overlay_undo_stack: Vec<(&'static str, OverlaySnapshot)>,
+    overlay_redo_stack: Vec<(&'static str, OverlaySnapshot)>,
+    tree_type_filter: TreeTypeFilter,
+    splash_started: Instant,
+    startup_preload_rx: Option<mpsc::Receiver<StartupPreload>>,
+    proposal_export_mode: ProposalExportMode,
+    overlay_wire_start: Option<([f32; 2], Option<u64>)>,
+    show_preferences: bool,
+    tree_scroll_to_match: bool,
+    subtree_paste_target: Option<u64>,
+    autosave_retry_at: Option<Instant>,
+}
+
+impl AutoMateApp {
+    fn new(cc: &CreationContext<'_>) -> Self {
+        cc.egui_ctx.set_visuals(egui::Visuals::dark());
+        let preferences = AppPreferences::load();
+        cc.egui_ctx.set_pixels_per_point(preferences.ui_scale);
+        let saved_project_paths = preferences.saved_project_paths.clone();
+        let mut app = Self {
+            tab: Tab::LineAudit,
+            dark_mode: true,
+            ui_density: 1.0,
+            search: String::new(),
+            reviews: seed_reviews(),
+            refactor_steps: vec![
+                "Split monolithic model into core domain structs + dedicated services",
+                "Extract estimating engine into testable pure module",
+                "Replace scattered state mutations with command-style actions",
+                "Consolidate export flow behind unified proposal pipeline",
+                "Normalize naming and remove duplicate data fields",
+                "Introduce view-model layer between UI and domain",
+                "Add project diagnostics panel with deterministic checks",
+                "Capture performance metrics for large drawings",
+            ],
+            features: seed_features(),
+            dependencies: seed_dependencies(),
+            pending_template_reset: None,
+            overlay_search: String::new(),
+            overlay_search_cursor: 0,
+            overlay_highlight: None,
+            dirty: false,
+            pending_discard: None,
+            close_confirmed: false,
+            retag_skip_manual: true,
+            retag_preview: None,
+            overlay_context_target: None,
+            overlay_drag_node: None,
+            overlay_selection: BTreeSet::new(),
+            overlay_band_start: None,
+            overlay_bulk_type: ObjectType::Equipment,
+            overlay_page: 0,
+            overlay_page_count: 0,
+            calibration_active: false,
+            calibration_points: Vec::new(),
+            calibration_distance: 10.0,
+            calibration_unit: "ft".to_string(),
+            selected_objects: HashSet::new(),
+            tree_anchor: None,
+            bulk_move_target: None,
+            autosave_rx: None,
+            overlay_render_rx: None,
+            overlay_render_generation: 0,
+            recovery_candidates: Vec::new(),
+            preferences,
+            pending_template_import: None,
+            template_export_selection: HashSet::new(),
+            controller_templates: Vec::new(),
+            template_search: String::new(),
+            credentials: LocalCredentials::load(),
+            login_password_confirm: String::new(),
+            overlay_snap_to_grid: false,
+            project_undo: Vec::new(),
+            project_redo: Vec::new(),
+            snapshot_label: String::new(),
+            points_table_sort: PointsSort::Name,
+            points_table_ascending: true,
+            tree_focused: false,
+            collapse_before_search: None,
+            bulk_equipment: None,
+            validation_report: None,
+            drawing_pdf_bytes: Vec::new(),
+            overlay_texture_scale: 1.0,
+            company_logo_bytes: std::fs::read(AppPreferences::logo_path()).ok(),
+            company_logo_texture: None,
+            wire_snap_to_nodes: true,
+            overlay_undo_stack: Vec::new(),
+            overlay_redo_stack: Vec::new(),
+            tree_type_filter: TreeTypeFilter::default(),
+            splash_started: Instant::now(),
+            startup_preload_rx: Some(StartupPreload::spawn(saved_project_paths)),
+            proposal_export_mode: ProposalExportMode::Combined,
+            overlay_wire_start: None,
+            show_preferences: false,
+            tree_scroll_to_match: false,
+            subtree_paste_target: None,
+            autosave_retry_at: None,
+        };
+        app.login_username = app.preferences.last_operator_id.clone();
+        app
+    }
+
+    fn top_bar(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal_wrapped(|ui| {
+                ui.heading("🚀 AutoMate BAS Studio 2.0");
+                ui.separator();
+                ui.label("Reconstruction workspace");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .selectable_label(self.dark_mode, "Dark")
+                        .on_hover_text("Toggle theme")
+                        .clicked()
+                    {
+                        self.dark_mode = !self.dark_mode;
+                        if self.dark_mode {
+                            ctx.set_visuals(egui::Visuals::dark());
+                        } else {
+                            ctx.set_visuals(egui::Visuals::light());
+                        }
+                    }
+                    ui.add(egui::Slider::new(&mut self.ui_density, 0.8..=1.4).text("Density"));
+                });
+            });
+            ui.add_space(4.0);
+        });
+    }
+
+    fn side_nav(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::left("left_nav")
+            .resizable(true)
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.heading("Workspace");
+                ui.separator();
+                for tab in [Tab::LineAudit, Tab::Refactor, Tab::Features, Tab::Dependencies] {
+                    let selected = self.tab == tab;
+                    if ui.selectable_label(selected, tab.label()).clicked() {
+                        self.tab = tab;
+                    }
+                }
+                ui.separator();
+                ui.label("Global Search");
+                ui.text_edit_singleline(&mut self.search);
+                ui.add_space(8.0);
+                let completion = (self.refactor_steps.len() as f32 / 10.0).min(1.0);
+                ui.add(egui::ProgressBar::new(completion).text("2.0 Plan Progress"));
+                ui.small("Target: complete 10 strategic changes");
+            });
+    }
+
+    fn central_ui(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(10.0 * self.ui_density, 8.0 * self.ui_density);
+            match self.tab {
+                Tab::LineAudit => self.render_line_audit(ui),
+                Tab::Refactor => self.render_refactor(ui),
+                Tab::Features => self.render_features(ui),
+                Tab::Dependencies => self.render_dependencies(ui),
+            }
+        });
+    }
+
+    fn render_line_audit(&self, ui: &mut egui::Ui) {
+        ui.heading("main.rs line-by-line audit summary");
+        ui.label("Each entry captures what should be kept, improved, or removed.");
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for entry in self.filtered_reviews() {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(entry.area);
+                        let chip = if entry.keep { "KEEP" } else { "CHANGE" };
+                        let color = if entry.keep {
+                            egui::Color32::from_rgb(40, 180, 110)
+                        } else {
+                            egui::Color32::from_rgb(225, 135, 65)
+                        };
+                        ui.colored_label(color, chip);
+                    });
+                    ui.label(format!("Finding: {}", entry.finding));
+                    ui.label(format!("Action: {}", entry.action));
+                });
+            }
+        });
+    }
+
+    fn filtered_reviews(&self) -> Vec<&LineReview> {
+        self.reviews
+            .iter()
+            .filter(|r| {
+                self.search.is_empty()
+                    || r.area.to_lowercase().contains(&self.search.to_lowercase())
+                    || r.finding.to_lowercase().contains(&self.search.to_lowercase())
+                    || r.action.to_lowercase().contains(&self.search.to_lowercase())
+            })
+            .collect()
+    }
+
+    fn render_refactor(&self, ui: &mut egui::Ui) {
+        ui.heading("Reconstruction blueprint");
+        ui.label("A practical sequence to evolve main.rs into a maintainable platform.");
+        ui.separator();
+
+        for (idx, step) in self.refactor_steps.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{:02}", idx + 1));
+                ui.label(*step);
+            });
+        }
+    }
+
+    fn render_features(&self, ui: &mut egui::Ui) {
+        ui.heading("10 new features for 2.0");
+        ui.separator();
+
+        egui::Grid::new("features_grid")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.strong("Feature");
+                ui.strong("User Value");
+                ui.strong("Complexity");
+                ui.end_row();
+
+                for feature in &self.features {
+                    ui.label(feature.name);
+                    ui.label(feature.value);
+                    ui.label(feature.complexity);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn render_dependencies(&self, ui: &mut egui::Ui) {
+        ui.heading("Cargo dependency decisions");
+        ui.label("Curated keep/remove/add strategy for a faster and cleaner build graph.");
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for dep in &self.dependencies {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(dep.crate_name);
+                        ui.label("→");
+                        ui.colored_label(egui::Color32::LIGHT_BLUE, dep.decision);
+                    });
+                    ui.label(dep.rationale);
+                });
+            }
+        });
+    }
+
+    fn feature_control_center_view(&mut self, ui: &mut Ui) {
+        ui.heading("Feature Control Center");
+        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
+        ui.add_space(8.0);
+
+        let (ready_count, ready_total) = self.export_readiness_score();
+        let overlay_ready = self.project.overlay_pdf.is_some();
+        let template_ready = !self.user_templates.is_empty();
+        let has_equipment = self
+            .project
+            .objects
+            .iter()
+            .any(|o| o.object_type == ObjectType::Equipment);
+
+        Self::card_frame().show(ui, |ui| {
+            ui.label(RichText::new("Core Capabilities").strong());
+            ui.separator();
+            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
+            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
+            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
+        });
+
+        ui.add_space(10.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Go to Project Settings").clicked() {
+                self.current_view = ToolView::ProjectSettings;
+            }
+            if ui.button("Go to Hours Estimator").clicked() {
+                self.current_view = ToolView::HoursEstimator;
+            }
+            if ui.button("Go to Drawings Overlay").clicked() {
+                self.current_view = ToolView::DrawingsOverlay;
+            }
+            if ui.button("Go to Templates").clicked() {
+                self.current_view = ToolView::Templates;
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Run Health Check").clicked() {
+                self.status = match self.project_health_summary() {
+                    Ok(summary) => summary,
+                    Err(err) => format!("Health check failed: {err:#}"),
+                };
+            }
+            if ui.button("Validate Export Readiness").clicked() {
+                self.status = match self.validate_export_readiness() {
+                    Ok(_) => "Export package ready".to_string(),
+                    Err(err) => err,
+                };
+            }
+            if ui.button("Export Proposal Markdown").clicked() {
+                self.export_proposal_markdown();
+            }
+            if ui.button("Export Objects CSV").clicked() {
+                self.export_objects_csv();
+            }
+            if ui.button("Export Project Schema").clicked() {
+                self.export_project_schema();
+            }
+            self.control_center_extra_actions(ui);
+        });
+    }
+
+    fn feature_control_center_view(&mut self, ui: &mut Ui) {
+        ui.heading("Feature Control Center");
+        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
+        ui.add_space(8.0);
+
+        let (ready_count, ready_total) = self.export_readiness_score();
+        let overlay_ready = self.project.overlay_pdf.is_some();
+        let template_ready = !self.user_templates.is_empty();
+        let has_equipment = self
+            .project
+            .objects
+            .iter()
+            .any(|o| o.object_type == ObjectType::Equipment);
+
+        Self::card_frame().show(ui, |ui| {
+            ui.label(RichText::new("Core Capabilities").strong());
+            ui.separator();
+            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
+            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
+            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
+        });
+
+        ui.add_space(10.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Go to Project Settings").clicked() {
+                self.current_view = ToolView::ProjectSettings;
+            }
+            if ui.button("Go to Hours Estimator").clicked() {
+                self.current_view = ToolView::HoursEstimator;
+            }
+            if ui.button("Go to Drawings Overlay").clicked() {
+                self.current_view = ToolView::DrawingsOverlay;
+            }
+            if ui.button("Go to Templates").clicked() {
+                self.current_view = ToolView::Templates;
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Run Health Check").clicked() {
+                self.status = match self.project_health_summary() {
+                    Ok(summary) => summary,
+                    Err(err) => format!("Health check failed: {err:#}"),
+                };
+            }
+            if ui.button("Validate Export Readiness").clicked() {
+                self.status = match self.validate_export_readiness() {
+                    Ok(_) => "Export package ready".to_string(),
+                    Err(err) => err,
+                };
+            }
+            if ui.button("Export Proposal Markdown").clicked() {
+                self.export_proposal_markdown();
+            }
+            if ui.button("Export Objects CSV").clicked() {
+                self.export_objects_csv();
+            }
+            if ui.button("Export Project Schema").clicked() {
+                self.export_project_schema();
+            }
+            self.control_center_extra_actions(ui);
+        });
+    }
+
+    fn feature_control_center_view(&mut self, ui: &mut Ui) {
+        ui.heading("Feature Control Center");
+        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
+        ui.add_space(8.0);
+
+        let (ready_count, ready_total) = self.export_readiness_score();
+        let overlay_ready = self.project.overlay_pdf.is_some();
+        let template_ready = !self.user_templates.is_empty();
+        let has_equipment = self
+            .project
+            .objects
+            .iter()
+            .any(|o| o.object_type == ObjectType::Equipment);
+
+        Self::card_frame().show(ui, |ui| {
+            ui.label(RichText::new("Core Capabilities").strong());
+            ui.separator();
+            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
+            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
+            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
+            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
+        });
+
+        ui.add_space(10.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Go to Project Settings").clicked() {
+                self.current_view = ToolView::ProjectSettings;
+            }
+            if ui.button("Go to Hours Estimator").clicked() {
+                self.current_view = ToolView::HoursEstimator;
+            }
+            if ui.button("Go to Drawings Overlay").clicked() {
+                self.current_view = ToolView::DrawingsOverlay;
+            }
+            if ui.button("Go to Templates").clicked() {
+                self.current_view = ToolView::Templates;
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("Run Health Check").clicked() {
+                self.status = match self.project_health_summary() {
+                    Ok(summary) => summary,
+                    Err(err) => format!("Health check failed: {err:#}"),
+                };
+            }
+            if ui.button("Validate Export Readiness").clicked() {
+                self.status = match self.validate_export_readiness() {
+                    Ok(_) => "Export package ready".to_string(),
+                    Err(err) => err,
+                };
+            }
+            if ui.button("Export Proposal Markdown").clicked() {
+                self.export_proposal_markdown();
+            }
+            if ui.button("Export Objects CSV").clicked() {
+                self.export_objects_csv();
+            }
+            if ui.button("Export Project Schema").clicked() {
+                self.export_project_schema();
+            }
+            self.control_center_extra_actions(ui);
+        });
+    }
+
+    fn commercial_terms_card(&mut self, ui: &mut Ui, labor: f32) {
+        let terms = &mut self.project.commercial;
+        let mut changed = false;
+        Self::card_frame().show(ui, |ui| {
+            ui.label(RichText::new("Markup & Tax").strong());
+            ui.separator();
+            egui::Grid::new("commercial_terms_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Materials / licenses ($)");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut terms.material_subtotal).speed(10.0).range(0.0..=f32::MAX))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Markup (%)");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut terms.markup_percent).speed(0.25).range(0.0..=100.0))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Tax (%)");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut terms.tax_percent).speed(0.05).range(0.0..=25.0))
+                        .changed();
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut terms.labor_taxable, "Labor taxable").changed();
+                changed |= ui.checkbox(&mut terms.materials_taxable, "Materials taxable").changed();
+            });
+            ui.separator();
+            let summary = CostSummary::new(labor, terms);
+            egui::Grid::new("cost_summary_grid")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (label, value) in cost_summary_rows(&summary) {
+                        ui.label(label);
+                        ui.monospace(format_currency(value));
+                        ui.end_row();
+                    }
+                });
+        });
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    fn template_reset_controls(&mut self, ui: &mut Ui, id: u64) {
+        let Some(obj) = self.project.objects.iter().find(|o| o.id == id) else {
+            return;
+        };
+        if obj.object_type != ObjectType::Equipment || obj.template_name.trim().is_empty() {
+            return;
+        }
+        let has_overrides = obj.hours_override || obj.equipment_type_override;
+        if ui
+            .add_enabled(has_overrides, egui::Button::new("↺ Reset to Template"))
+            .on_hover_text("Clear overrides and re-sync hours and type from the assigned template")
+            .clicked()
+        {
+            self.pending_template_reset = Some(id);
+        }
+    }
+
+    fn reset_object_to_template(&mut self, id: u64) {
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) else {
+            return;
+        };
+        obj.hours_override = false;
+        obj.equipment_type_override = false;
+        let name = obj.name.clone();
+        self.sync_equipment_from_template(id);
+        self.mark_dirty();
+        self.status = format!("{name} reset to template values");
+    }
+
+    fn template_reset_dialog(&mut self, ctx: &egui::Context) {
+        let Some(id) = self.pending_template_reset else {
+            return;
+        };
+        let name = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == id)
+            .map(|o| o.name.clone())
+            .unwrap_or_default();
+        let mut open = true;
+        let mut decided = false;
+        egui::Window::new("Reset to Template")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Reset {name} to its template? Manual hour and type overrides will be discarded."
+                ));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Reset").clicked() {
+                        self.reset_object_to_template(id);
+                        decided = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        decided = true;
+                    }
+                });
+            });
+        if decided || !open {
+            self.pending_template_reset = None;
+        }
+    }
+
+    fn overlay_node_label(&self, node: &OverlayNode) -> String {
+        self.project
+            .objects
+            .iter()
+            .find(|o| o.id == node.object_id)
+            .map(|o| {
+                if o.equipment_tag.trim().is_empty() {
+                    o.name.clone()
+                } else {
+                    format!("{} ({})", o.name, o.equipment_tag)
+                }
+            })
+            .unwrap_or_else(|| format!("#{}", node.object_id))
+    }
+
+    fn export_marked_up_drawing(&mut self) {
+        if self.overlay_pdf_bytes.is_none() {
+            self.status = "Load a drawing PDF before exporting a marked-up copy".to_string();
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PDF", &["pdf"])
+            .set_file_name("marked_up_drawing.pdf")
+            .save_file()
+        else {
+            return;
+        };
+        self.status = match self.write_marked_up_drawing(&path) {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported marked-up drawing");
+                format!("Marked-up drawing exported: {}", path.display())
+            }
+            Err(err) => format!("Marked-up drawing export failed: {err:#}"),
+        };
+    }
+
+    /// Renders the current page like `refresh_overlay_texture` and burns that page's
+    /// tokens and wires on top.
+    /// Overlay coordinates are page-space pixels at the page's point width, so one pixel
+    /// maps to one PDF point with no zoom correction.
+    fn write_marked_up_drawing(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        use anyhow::Context;
+        use printpdf::{BuiltinFont, Color, ColorBits, ColorSpace, Image, ImageTransform};
+        use printpdf::{ImageXObject, Line, Mm, PdfDocument, Point, Px, Rgb};
+
+        let bytes = self
+            .overlay_pdf_bytes
+            .as_deref()
+            .context("no drawing PDF loaded")?;
+        let pdfium = bind_pdfium()?;
+        let document = pdfium
+            .load_pdf_from_byte_slice(bytes, None)
+            .context("failed to open drawing PDF")?;
+        let page = document
+            .pages()
+            .get(self.overlay_page as u16)
+            .context("drawing page out of range")?;
+        let config = pdfium_render::prelude::PdfRenderConfig::new()
+            .set_target_width(page.width().value as i32)
+            .render_form_data(self.preferences.render_pdf_form_data);
+        let rendered = page
+            .render_with_config(&config)
+            .context("failed to render drawing page")?
+            .as_image()
+            .to_rgb8();
+        let (width, height) = rendered.dimensions();
+
+        let px_to_mm = |v: f32| Mm(v * 25.4 / 72.0);
+        let page_h = height as f32;
+        let (doc, page_idx, layer_idx) =
+            PdfDocument::new("Marked-up drawing", px_to_mm(width as f32), px_to_mm(page_h), "Drawing");
+        let layer = doc.get_page(page_idx).get_layer(layer_idx);
+        Image::from(ImageXObject {
+            width: Px(width as usize),
+            height: Px(height as usize),
+            color_space: ColorSpace::Rgb,
+            bits_per_component: ColorBits::Bit8,
+            interpolate: true,
+            image_data: rendered.into_raw(),
+            image_filter: None,
+            smask: None,
+            clipping_bbox: None,
+        })
+        .add_to_layer(
+            layer.clone(),
+            ImageTransform {
+                dpi: Some(72.0),
+                ..Default::default()
+            },
+        );
+
+        let point = |x: f32, y: f32| Point::new(px_to_mm(x), px_to_mm(page_h - y));
+        layer.set_outline_color(Color::Rgb(Rgb::new(0.1, 0.45, 0.95, None)));
+        layer.set_outline_thickness(2.0);
+        for line in self.project.overlay_lines.iter().filter(|l| l.page == self.overlay_page) {
+            layer.add_line(Line {
+                points: vec![
+                    (point(line.from[0], line.from[1]), false),
+                    (point(line.to[0], line.to[1]), false),
+                ],
+                is_closed: false,
+            });
+        }
+
+        let font = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .context("failed to load PDF font")?;
+        layer.set_fill_color(Color::Rgb(Rgb::new(0.85, 0.1, 0.1, None)));
+        for node in self.project.overlay_nodes.iter().filter(|n| n.page == self.overlay_page) {
+            let (x, y) = (px_to_mm(node.x), px_to_mm(page_h - node.y));
+            layer.use_text(self.overlay_node_label(node), 9.0, x, y, &font);
+        }
+
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        doc.save(&mut std::io::BufWriter::new(file))
+            .context("failed to write marked-up PDF")?;
+        Ok(())
+    }
+
+    fn overlay_search_matches(&self) -> Vec<usize> {
+        let query = self.overlay_search.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        self.project
+            .overlay_nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                self.project
+                    .objects
+                    .iter()
+                    .find(|o| o.id == node.object_id)
+                    .is_some_and(|o| object_matches_query(o, query))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Search box for the overlay toolbar. Enter cycles through matches and centers
+    /// the view on the current one.
+    fn overlay_search_bar(&mut self, ui: &mut Ui, viewport: egui::Vec2) {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.overlay_search)
+                .hint_text("🔍 Find token")
+                .desired_width(160.0),
+        );
+        if response.changed() {
+            self.overlay_search_cursor = 0;
+        }
+        let matches = self.overlay_search_matches();
+        if matches.is_empty() {
+            self.overlay_highlight = None;
+            if !self.overlay_search.trim().is_empty() {
+                ui.small("No matches");
+            }
+            return;
+        }
+        let submitted =
+            response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if submitted || response.changed() {
+            if submitted && self.overlay_highlight.is_some() {
+                self.overlay_search_cursor = (self.overlay_search_cursor + 1) % matches.len();
+            }
+            let idx = matches[self.overlay_search_cursor.min(matches.len() - 1)];
+            self.focus_overlay_node(idx, viewport);
+            if submitted {
+                response.request_focus();
+            }
+        }
+        ui.small(format!(
+            "{}/{}",
+            self.overlay_search_cursor.min(matches.len() - 1) + 1,
+            matches.len()
+        ));
+    }
+
+    fn focus_overlay_node(&mut self, idx: usize, viewport: egui::Vec2) {
+        let Some(node) = self.project.overlay_nodes.get(idx) else {
+            return;
+        };
+        let page = node.page;
+        let target = egui::vec2(node.x, node.y);
+        if page != self.overlay_page {
+            self.overlay_page = page;
+            self.request_overlay_render();
+        }
+        self.overlay_zoom = self.overlay_zoom.max(1.5);
+        self.overlay_pan = viewport * 0.5 - target * self.overlay_zoom;
+        self.overlay_highlight = Some(idx);
+    }
+
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// The saved file matches memory again. `save_project_to_path` and
+    /// `load_project_from_path` call this themselves.
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Runs `action` immediately when the project is clean, otherwise asks first.
+    fn request_discard(&mut self, ctx: &egui::Context, action: DiscardAction) {
+        if self.dirty {
+            self.pending_discard = Some(action);
+        } else {
+            self.perform_discard_action(ctx, action);
+        }
+    }
+
+    fn perform_discard_action(&mut self, ctx: &egui::Context, action: DiscardAction) {
+        match action {
+            DiscardAction::NewProject => {
+                self.new_project();
+                self.mark_clean();
+            }
+            DiscardAction::LoadProject => self.load_project(),
+            DiscardAction::CloseWindow => {
+                self.close_confirmed = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            DiscardAction::LogOut => self.log_out(),
+            DiscardAction::ImportProjectJson => self.import_project_json(),
+            DiscardAction::NewFromPreset => self.new_project_from_preset(),
+        }
+    }
+
+    /// Returns to the login card. The project stays loaded so the next operator to
+    /// sign in on this workstation picks up where it was left.
+    fn log_out(&mut self) {
+        self.app_screen = AppScreen::Login;
+        self.login_password.clear();
+        self.login_password_confirm.clear();
+        // Forces `configure_viewport_for_screen` to shrink the window back to the login card.
+        self.viewport_configured_for = None;
+        self.status = "Logged out".to_string();
+    }
+
+    fn intercept_close_request(&mut self, ctx: &egui::Context) {
+        if !ctx.input(|i| i.viewport().close_requested()) {
+            return;
+        }
+        if self.dirty && !self.close_confirmed {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.pending_discard = Some(DiscardAction::CloseWindow);
+        }
+    }
+
+    fn unsaved_changes_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.pending_discard else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Unsaved Changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} has unsaved changes. Save them before {}?",
+                    self.project.name,
+                    action.label()
+                ));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.pending_discard = None;
+                    }
+                });
+            });
+        match choice {
+            Some(true) => {
+                self.save_project();
+                // A cancelled save dialog leaves the project dirty; keep the prompt open.
+                if !self.dirty {
+                    self.pending_discard = None;
+                    self.perform_discard_action(ctx, action);
+                }
+            }
+            Some(false) => {
+                self.pending_discard = None;
+                self.dirty = false;
+                self.perform_discard_action(ctx, action);
+            }
+            None => {}
+        }
+    }
+
+    /// Extra Project-menu entries rendered by `toolbar_dropdowns` under Save.
+    fn project_menu_extras(&mut self, ui: &mut Ui) {
+        if ui.button("💾 Save As…").clicked() {
+            self.save_project_as();
+            ui.close_menu();
+        }
+        if ui
+            .button("🔀 New Revision")
+            .on_hover_text("Branch this job as the next proposal revision")
+            .clicked()
+        {
+            self.new_revision();
+            ui.close_menu();
+        }
+    }
+
+    /// Branches the open project: same objects, overlay and estimator settings, but a
+    /// fresh identity and no file, so the next Save asks where the revision goes.
+    fn new_revision(&mut self) {
+        self.project.project_uuid = uuid::Uuid::new_v4();
+        self.project.proposal.revision += 1;
+        self.project_path = None;
+        self.mark_dirty();
+        self.status = format!(
+            "{} — Rev {} (unsaved)",
+            self.project.name, self.project.proposal.revision
+        );
+    }
+
+    /// Always prompts for a destination, unlike `save_project` which reuses `project_path`.
+    fn save_project_as(&mut self) {
+        let default_name = format!("{}.m8", sanitize_file_stem(&self.project.name));
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("AutoMate Project", &["m8"])
+            .set_file_name(default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let (path, corrected) = with_m8_extension(path);
+        match self.save_project_to_path(&path) {
+            Ok(()) => {
+                self.status = if corrected {
+                    format!("Saved as {} (added .m8 so it shows in Load)", path.display())
+                } else {
+                    format!("Saved as {}", path.display())
+                };
+                self.project_path = Some(path);
+            }
+            Err(err) => self.status = format!("Save failed: {err}"),
+        }
+    }
+
+    fn handle_save_as_shortcut(&mut self, ctx: &egui::Context) {
+        let save_as = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::S,
+        );
+        if self.app_screen == AppScreen::Studio
+            && ctx.input_mut(|i| i.consume_shortcut(&save_as))
+        {
+            self.save_project_as();
+        }
+    }
+
+    /// Dry run of the equipment retag: every tag that would change, nothing applied.
+    /// Equipment is numbered in tree order within each type using `project.tag_scheme`;
+    /// kept manual tags reserve their sequence number so nothing is issued twice.
+    fn plan_equipment_tags(&self) -> Vec<TagChange> {
+        let scheme = &self.project.tag_scheme;
+        let equipment: Vec<&BasObject> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Equipment)
+            .collect();
+        let keep = |o: &BasObject| self.retag_skip_manual && !o.equipment_tag.trim().is_empty();
+        let mut reserved: HashSet<(String, u32)> = HashSet::new();
+        for obj in equipment.iter().filter(|o| keep(o)) {
+            let prefix = scheme.prefix_for(&obj.equipment_type);
+            if let Some(seq) = scheme.sequence_of(&prefix, &obj.equipment_tag) {
+                reserved.insert((prefix, seq));
+            }
+        }
+        let mut next: HashMap<String, u32> = HashMap::new();
+        let mut changes = Vec::new();
+        for obj in equipment.into_iter().filter(|o| !keep(o)) {
+            let prefix = scheme.prefix_for(&obj.equipment_type);
+            let counter = next.entry(prefix.clone()).or_insert(1);
+            while reserved.contains(&(prefix.clone(), *counter)) {
+                *counter += 1;
+            }
+            let new_tag = scheme.format(&prefix, *counter);
+            *counter += 1;
+            if new_tag != obj.equipment_tag {
+                changes.push(TagChange {
+                    object_id: obj.id,
+                    name: obj.name.clone(),
+                    old_tag: obj.equipment_tag.clone(),
+                    new_tag,
+                });
+            }
+        }
+        changes
+    }
+
+    fn apply_tag_changes(&mut self, changes: &[TagChange]) {
+        for change in changes {
+            if let Some(obj) = self
+                .project
+                .objects
+                .iter_mut()
+                .find(|o| o.id == change.object_id)
+            {
+                obj.equipment_tag = change.new_tag.clone();
+            }
+        }
+        if !changes.is_empty() {
+            self.mark_dirty();
+        }
+        self.status = format!("Retagged {} equipment", changes.len());
+    }
+
+    fn retag_preview_dialog(&mut self, ctx: &egui::Context) {
+        let Some(changes) = self.retag_preview.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        let mut skip_manual = self.retag_skip_manual;
+        let mut scheme = self.project.tag_scheme.clone();
+        egui::Window::new("Retag Equipment — Preview")
+            .collapsible(false)
+            .default_size([520.0, 420.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Separator");
+                    ui.add(egui::TextEdit::singleline(&mut scheme.separator).desired_width(30.0));
+                    ui.label("Digits");
+                    ui.add(egui::DragValue::new(&mut scheme.padding).range(1..=6));
+                    ui.small(format!("e.g. {}", scheme.format("VAV", 1)));
+                });
+                ui.checkbox(&mut skip_manual, "Keep tags that are already set")
+                    .on_hover_text("Uncheck to renumber every equipment from 1 within its type");
+                ui.label(format!("{} tag(s) will change", changes.len()));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("retag_preview_grid")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.strong("Equipment");
+                            ui.strong("Current");
+                            ui.strong("Proposed");
+                            ui.end_row();
+                            for change in &changes {
+                                ui.label(&change.name);
+                                ui.monospace(if change.old_tag.is_empty() {
+                                    "—"
+                                } else {
+                                    change.old_tag.as_str()
+                                });
+                                ui.monospace(&change.new_tag);
+                                ui.end_row();
+                            }
+                        });
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!changes.is_empty(), egui::Button::new("✔ Commit"))
+                        .clicked()
+                    {
+                        self.apply_tag_changes(&changes);
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if close || !open {
+            self.retag_preview = None;
+        } else if skip_manual != self.retag_skip_manual || scheme != self.project.tag_scheme {
+            self.retag_skip_manual = skip_manual;
+            if scheme != self.project.tag_scheme {
+                self.project.tag_scheme = scheme;
+                self.mark_dirty();
+            }
+            self.retag_preview = Some(self.plan_equipment_tags());
+        }
+    }
+
+    fn open_retag_preview(&mut self) {
+        self.retag_preview = Some(self.plan_equipment_tags());
+    }
+
+    /// Topmost token or wire under `screen_pos`, using the same zoom/pan transform
+    /// `drawings_overlay_view` draws with. Tokens win over wires.
+    fn overlay_hit_test(&self, canvas_min: egui::Pos2, screen_pos: egui::Pos2) -> Option<OverlayTarget> {
+        let to_screen =
+            |x: f32, y: f32| overlay_to_screen(canvas_min, self.overlay_pan, self.overlay_zoom, x, y);
+        if let Some(idx) = self
+            .project
+            .overlay_nodes
+            .iter()
+            .rposition(|n| {
+                n.page == self.overlay_page
+                    && to_screen(n.x, n.y).distance(screen_pos) <= OVERLAY_HIT_RADIUS
+            })
+        {
+            return Some(OverlayTarget::Node(idx));
+        }
+        self.project
+            .overlay_lines
+            .iter()
+            .rposition(|l| {
+                if l.page != self.overlay_page {
+                    return false;
+                }
+                let a = to_screen(l.from[0], l.from[1]);
+                let b = to_screen(l.to[0], l.to[1]);
+                distance_to_segment(screen_pos, a, b) <= OVERLAY_HIT_RADIUS * 0.5
+            })
+            .map(OverlayTarget::Line)
+    }
+
+    /// Right-click menu for the overlay canvas: delete the token or wire under the cursor.
+    fn overlay_context_menu(&mut self, response: &egui::Response, canvas_min: egui::Pos2) {
+        if response.secondary_clicked() {
+            self.overlay_context_target = response
+                .interact_pointer_pos()
+                .and_then(|pos| self.overlay_hit_test(canvas_min, pos));
+        }
+        let Some(target) = self.overlay_context_target else {
+            return;
+        };
+        response.context_menu(|ui| match target {
+            OverlayTarget::Node(idx) => {
+                let label = self
+                    .project
+                    .overlay_nodes
+                    .get(idx)
+                    .map(|n| self.overlay_node_label(n))
+                    .unwrap_or_default();
+                ui.label(RichText::new(label).strong());
+                let current = self.project.overlay_nodes.get(idx).map(|n| n.status);
+                ui.menu_button("Set Status", |ui| {
+                    for status in OverlayStatus::iter() {
+                        let text = RichText::new(status.label()).color(status.color());
+                        if ui.radio(current == Some(status), text).clicked() {
+                            self.set_overlay_status(&[idx], status);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.separator();
+                if ui.button("🗑 Delete Token").clicked() {
+                    self.delete_overlay_target(target);
+                    ui.close_menu();
+                }
+            }
+            OverlayTarget::Line(_) => {
+                if ui.button("🗑 Delete Wire").clicked() {
+                    self.delete_overlay_target(target);
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    fn delete_overlay_target(&mut self, target: OverlayTarget) {
+        self.push_overlay_history_as("Deleted overlay item");
+        match target {
+            OverlayTarget::Node(idx) if idx < self.project.overlay_nodes.len() => {
+                self.project.overlay_nodes.remove(idx);
+                self.status = "Token deleted".to_string();
+            }
+            OverlayTarget::Line(idx) if idx < self.project.overlay_lines.len() => {
+                self.project.overlay_lines.remove(idx);
+                self.status = "Wire deleted".to_string();
+            }
+            _ => {}
+        }
+        self.overlay_context_target = None;
+        self.overlay_highlight = None;
+        self.overlay_selection.clear();
+        self.mark_dirty();
+    }
+
+    /// Primary-button drag on a placed token moves it in page space. History is pushed
+    /// once when the drag starts so the whole move is a single undo step. Middle-drag
+    /// panning is left untouched. Returns `true` while a token drag owns the pointer.
+    fn overlay_token_drag(&mut self, response: &egui::Response, canvas_min: egui::Pos2) -> bool {
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            let hit = response
+                .interact_pointer_pos()
+                .and_then(|pos| self.overlay_hit_test(canvas_min, pos));
+            if let Some(OverlayTarget::Node(idx)) = hit {
+                self.push_overlay_history_as("Moved token");
+                self.overlay_drag_node = Some(idx);
+            }
+        }
+        let Some(idx) = self.overlay_drag_node else {
+            return false;
+        };
+        if response.dragged_by(egui::PointerButton::Primary) {
+            let delta = response.drag_delta() / self.overlay_zoom.max(f32::EPSILON);
+            if let Some(node) = self.project.overlay_nodes.get_mut(idx) {
+                node.x += delta.x;
+                node.y += delta.y;
+            }
+            self.follow_wire_ends();
+        }
+        if response.drag_stopped() {
+            if let Some(node) = self.project.overlay_nodes.get_mut(idx) {
+                [node.x, node.y] = snap_overlay_point(self.overlay_snap_step(), [node.x, node.y]);
+            }
+            self.follow_wire_ends();
+            self.overlay_drag_node = None;
+            self.mark_dirty();
+        }
+        true
+    }
+
+    /// Driver controls for a template card in `templates_view`.
+    fn template_driver_editor(ui: &mut Ui, template: &mut EquipmentTemplate) -> bool {
+        let mut changed = false;
+        egui::Grid::new(("template_drivers", template.name.as_str()))
+            .num_columns(3)
+            .show(ui, |ui| {
+                ui.label("Graphics");
+                changed |= hour_driver_combo(ui, ("gfx_driver", &template.name), &mut template.graphics_driver);
+                ui.add_enabled_ui(template.graphics_driver == HourDriver::PerUnit, |ui| {
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut template.graphic_pages).suffix(" pages"))
+                        .changed();
+                });
+                ui.end_row();
+                ui.label("Commissioning");
+                changed |= hour_driver_combo(ui, ("cx_driver", &template.name), &mut template.commissioning_driver);
+                ui.add_enabled_ui(template.commissioning_driver == HourDriver::PerUnit, |ui| {
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut template.commissioning_tests).suffix(" tests"))
+                        .changed();
+                });
+                ui.end_row();
+            });
+        changed
+    }
+
+    /// Primary drag on empty canvas draws a selection band; releasing selects every
+    /// token whose center falls inside it (screen space, so zoom and pan are implied).
+    fn overlay_rubber_band(
+        &mut self,
+        response: &egui::Response,
+        painter: &egui::Painter,
+        canvas_min: egui::Pos2,
+    ) {
+        if self.overlay_tool == OverlayTool::Route || self.overlay_drag_node.is_some() {
+            return;
+        }
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            let start = response.interact_pointer_pos();
+            let on_empty = start.is_some_and(|pos| self.overlay_hit_test(canvas_min, pos).is_none());
+            self.overlay_band_start = start.filter(|_| on_empty);
+        }
+        let Some(start) = self.overlay_band_start else {
+            return;
+        };
+        let current = response.interact_pointer_pos().unwrap_or(start);
+        let band = egui::Rect::from_two_pos(start, current);
+        painter.rect(
+            band,
+            0.0,
+            Color32::from_rgba_unmultiplied(self.accent().r(), self.accent().g(), self.accent().b(), 40),
+            egui::Stroke::new(1.0, self.accent()),
+        );
+        if response.drag_stopped() {
+            let additive = response.ctx.input(|i| i.modifiers.shift);
+            if !additive {
+                self.overlay_selection.clear();
+            }
+            for (idx, node) in self.project.overlay_nodes.iter().enumerate() {
+                if node.page != self.overlay_page {
+                    continue;
+                }
+                let center =
+                    overlay_to_screen(canvas_min, self.overlay_pan, self.overlay_zoom, node.x, node.y);
+                if band.contains(center) {
+                    self.overlay_selection.insert(idx);
+                }
+            }
+            self.overlay_band_start = None;
+        }
+    }
+
+    /// Inspector tooltip for the token under the pointer, called from
+    /// `drawings_overlay_view` with the canvas response. Suppressed while dragging so it
+    /// doesn't trail a moving token.
+    fn overlay_token_tooltip(&self, response: &egui::Response, canvas_min: egui::Pos2) {
+        if self.overlay_drag_node.is_some() || self.overlay_band_start.is_some() {
+            return;
+        }
+        let Some(OverlayTarget::Node(idx)) = response
+            .hover_pos()
+            .and_then(|pos| self.overlay_hit_test(canvas_min, pos))
+        else {
+            return;
+        };
+        let Some(node) = self.project.overlay_nodes.get(idx) else {
+            return;
+        };
+        let object = self.project.objects.iter().find(|o| o.id == node.object_id);
+        response.clone().on_hover_ui_at_pointer(|ui| {
+            let Some(obj) = object else {
+                ui.label(RichText::new("Unlinked token").weak());
+                return;
+            };
+            ui.strong(format!("{} {}", obj.object_type.icon(), obj.name));
+            egui::Grid::new("overlay_token_tooltip").num_columns(2).show(ui, |ui| {
+                let or_dash = |value: &str| if value.trim().is_empty() { "—".to_string() } else { value.to_string() };
+                ui.label("Type");
+                ui.label(match obj.object_type {
+                    ObjectType::Equipment => or_dash(&obj.equipment_type),
+                    kind => format!("{kind:?}"),
+                });
+                ui.end_row();
+                ui.label("Tag");
+                ui.label(or_dash(&obj.equipment_tag));
+                ui.end_row();
+                if obj.object_type == ObjectType::Equipment {
+                    ui.label("Template");
+                    ui.label(or_dash(&obj.template_name));
+                    ui.end_row();
+                }
+                let points = match obj.object_type {
+                    ObjectType::Controller => self.controller_point_count(obj.id),
+                    _ => self
+                        .project
+                        .objects
+                        .iter()
+                        .filter(|o| o.parent_id == Some(obj.id) && o.object_type == ObjectType::Point)
+                        .count(),
+                };
+                ui.label("Points");
+                ui.label(points.to_string());
+                ui.end_row();
+            });
+        });
+    }
+
+    /// Removes every selected token as one undo step. Wires keep their last
+    /// coordinates, like deleting a single token.
+    fn delete_selected_overlay_nodes(&mut self) {
+        if self.overlay_selection.is_empty() {
+            return;
+        }
+        self.push_overlay_history_as("Deleted selected tokens");
+        let count = self.overlay_selection.len();
+        // Highest index first so earlier removals don't shift later ones.
+        for &idx in self.overlay_selection.iter().rev() {
+            if idx < self.project.overlay_nodes.len() {
+                self.project.overlay_nodes.remove(idx);
+            }
+        }
+        self.overlay_selection.clear();
+        self.overlay_highlight = None;
+        self.mark_dirty();
+        self.status = format!("Deleted {count} token(s)");
+    }
+
+    /// Aligns the selected tokens to the outermost one on an edge, or spaces them evenly
+    /// between the two outermost, in page space. One undo step.
+    fn arrange_selected_overlay_nodes(&mut self, arrange: OverlayArrange) {
+        let mut indices: Vec<usize> = self
+            .overlay_selection
+            .iter()
+            .copied()
+            .filter(|&idx| idx < self.project.overlay_nodes.len())
+            .collect();
+        if indices.len() < arrange.min_selection() {
+            return;
+        }
+        self.push_overlay_history_as("Arranged tokens");
+        let nodes = &mut self.project.overlay_nodes;
+        match arrange {
+            OverlayArrange::AlignLeft => {
+                let x = indices.iter().map(|&i| nodes[i].x).fold(f32::INFINITY, f32::min);
+                indices.iter().for_each(|&i| nodes[i].x = x);
+            }
+            OverlayArrange::AlignRight => {
+                let x = indices.iter().map(|&i| nodes[i].x).fold(f32::NEG_INFINITY, f32::max);
+                indices.iter().for_each(|&i| nodes[i].x = x);
+            }
+            OverlayArrange::AlignTop => {
+                let y = indices.iter().map(|&i| nodes[i].y).fold(f32::INFINITY, f32::min);
+                indices.iter().for_each(|&i| nodes[i].y = y);
+            }
+            OverlayArrange::AlignBottom => {
+                let y = indices.iter().map(|&i| nodes[i].y).fold(f32::NEG_INFINITY, f32::max);
+                indices.iter().for_each(|&i| nodes[i].y = y);
+            }
+            OverlayArrange::DistributeHorizontally | OverlayArrange::DistributeVertically => {
+                let horizontal = arrange == OverlayArrange::DistributeHorizontally;
+                let coord = |n: &OverlayNode| if horizontal { n.x } else { n.y };
+                indices.sort_by(|&a, &b| coord(&nodes[a]).total_cmp(&coord(&nodes[b])));
+                let first = coord(&nodes[indices[0]]);
+                let last = coord(&nodes[indices[indices.len() - 1]]);
+                let gap = (last - first) / (indices.len() - 1) as f32;
+                for (step, &i) in indices.iter().enumerate() {
+                    let value = first + gap * step as f32;
+                    if horizontal {
+                        nodes[i].x = value;
+                    } else {
+                        nodes[i].y = value;
+                    }
+                }
+            }
+        }
+        self.follow_wire_ends();
+        self.mark_dirty();
+        self.status = format!("{} — {} tokens", arrange.label(), indices.len());
+    }
+
+    /// Page-space distance of one nudge: a pixel, or a grid step with Shift.
+    fn overlay_nudge_step(&self, coarse: bool) -> f32 {
+        if coarse {
+            self.preferences.overlay_grid_step.max(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    fn nudge_selected_overlay_nodes(&mut self, delta: [f32; 2]) {
+        if self.overlay_selection.is_empty() {
+            return;
+        }
+        self.push_overlay_history_as("Nudged selected tokens");
+        for &idx in &self.overlay_selection {
+            if let Some(node) = self.project.overlay_nodes.get_mut(idx) {
+                node.x += delta[0];
+                node.y += delta[1];
+            }
+        }
+        self.follow_wire_ends();
+        self.mark_dirty();
+    }
+
+    /// Delete/Backspace and the arrow keys act on the rubber-band selection in
+    /// `drawings_overlay_view`. Ignored while a text field has focus.
+    fn overlay_selection_keys(&mut self, ctx: &egui::Context) {
+        if self.overlay_selection.is_empty() || ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let delete = ctx.input_mut(|i| {
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Delete)
+                || i.consume_key(egui::Modifiers::NONE, egui::Key::Backspace)
+        });
+        if delete {
+            self.delete_selected_overlay_nodes();
+            return;
+        }
+        let coarse = ctx.input(|i| i.modifiers.shift);
+        let step = self.overlay_nudge_step(coarse);
+        let modifiers = if coarse { egui::Modifiers::SHIFT } else { egui::Modifiers::NONE };
+        for (key, delta) in [
+            (egui::Key::ArrowLeft, [-step, 0.0]),
+            (egui::Key::ArrowRight, [step, 0.0]),
+            (egui::Key::ArrowUp, [0.0, -step]),
+            (egui::Key::ArrowDown, [0.0, step]),
+        ] {
+            if ctx.input_mut(|i| i.consume_key(modifiers, key)) {
+                self.nudge_selected_overlay_nodes(delta);
+            }
+        }
+    }
+
+    /// Accent rings around selected tokens, painted over the token layer.
+    fn paint_overlay_selection(&self, painter: &egui::Painter, canvas_min: egui::Pos2) {
+        let stroke = egui::Stroke::new(2.0, self.accent());
+        for &idx in &self.overlay_selection {
+            let Some(node) = self.project.overlay_nodes.get(idx).filter(|n| n.page == self.overlay_page) else {
+                continue;
+            };
+            let center = overlay_to_screen(canvas_min, self.overlay_pan, self.overlay_zoom, node.x, node.y);
+            painter.circle_stroke(center, OVERLAY_HIT_RADIUS + 3.0, stroke);
+        }
+    }
+
+    fn overlay_node_object_type(&self, node: &OverlayNode) -> Option<ObjectType> {
+        self.project
+            .objects
+            .iter()
+            .find(|o| o.id == node.object_id)
+            .map(|o| o.object_type)
+    }
+
+    /// Sets `status` on the given tokens as a single undo step.
+    fn set_overlay_status(&mut self, indices: &[usize], status: OverlayStatus) {
+        if indices.is_empty() {
+            return;
+        }
+        self.push_overlay_history_as("Changed token status");
+        for &idx in indices {
+            if let Some(node) = self.project.overlay_nodes.get_mut(idx) {
+                node.status = status;
+            }
+        }
+        self.mark_dirty();
+        self.status = format!("{} token(s) marked {}", indices.len(), status.label());
+    }
+
+    fn overlay_bulk_status_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            let selected: Vec<usize> = self.overlay_selection.iter().copied().collect();
+            ui.label(format!("Selected: {}", selected.len()));
+            if ui
+                .add_enabled(!selected.is_empty(), egui::Button::new("🗑 Delete selected"))
+                .clicked()
+            {
+                self.delete_selected_overlay_nodes();
+            }
+            ui.add_enabled_ui(!selected.is_empty(), |ui| {
+                ui.label("Nudge");
+                let step = self.overlay_nudge_step(ui.input(|i| i.modifiers.shift));
+                for (label, delta) in [("◀", [-step, 0.0]), ("▶", [step, 0.0]), ("▲", [0.0, -step]), ("▼", [0.0, step])] {
+                    if ui
+                        .small_button(label)
+                        .on_hover_text("Arrow keys also nudge; hold Shift for a grid step")
+                        .clicked()
+                    {
+                        self.nudge_selected_overlay_nodes(delta);
+                    }
+                }
+            });
+            ui.separator();
+            for status in OverlayStatus::iter() {
+                if ui
+                    .add_enabled(
+                        !selected.is_empty(),
+                        egui::Button::new(RichText::new(status.label()).color(status.color())),
+                    )
+                    .clicked()
+                {
+                    self.set_overlay_status(&selected, status);
+                }
+            }
+            ui.separator();
+            ui.menu_button("Arrange", |ui| {
+                for arrange in OverlayArrange::ALL {
+                    if ui
+                        .add_enabled(selected.len() >= arrange.min_selection(), egui::Button::new(arrange.label()))
+                        .clicked()
+                    {
+                        self.arrange_selected_overlay_nodes(arrange);
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.separator();
+            ui.label("All");
+            egui::ComboBox::from_id_source("overlay_bulk_type")
+                .selected_text(format!("{:?}", self.overlay_bulk_type))
+                .show_ui(ui, |ui| {
+                    for kind in [ObjectType::Controller, ObjectType::Equipment] {
+                        ui.selectable_value(&mut self.overlay_bulk_type, kind, format!("{kind:?}"));
+                    }
+                });
+            for status in OverlayStatus::iter() {
+                if ui
+                    .small_button(RichText::new(status.label()).color(status.color()))
+                    .clicked()
+                {
+                    let matching: Vec<usize> = self
+                        .project
+                        .overlay_nodes
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, n)| {
+                            n.page == self.overlay_page
+                                && self.overlay_node_object_type(n) == Some(self.overlay_bulk_type)
+                        })
+                        .map(|(idx, _)| idx)
+                        .collect();
+                    self.set_overlay_status(&matching, status);
+                }
+            }
+        });
+    }
+
+    /// Prev/Next page controls for multi-sheet drawings. `overlay_page_count` is
+    /// refreshed by `poll_overlay_render` whenever a page finishes rendering.
+    fn overlay_page_nav(&mut self, ui: &mut Ui) {
+        if self.overlay_page_count <= 1 {
+            return;
+        }
+        let mut page = self.overlay_page;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(page > 0, egui::Button::new("◀ Prev")).clicked() {
+                page -= 1;
+            }
+            ui.label(format!("Page {} / {}", page + 1, self.overlay_page_count));
+            if ui
+                .add_enabled(page + 1 < self.overlay_page_count, egui::Button::new("Next ▶"))
+                .clicked()
+            {
+                page += 1;
+            }
+        });
+        if page != self.overlay_page {
+            self.overlay_page = page;
+            self.overlay_selection.clear();
+            self.overlay_highlight = None;
+            self.request_overlay_render();
+        }
+    }
+
+    fn export_assignment_matrix(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("controller_assignment_matrix.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let result = assignment_matrix_csv(&self.project.objects)
+            .and_then(|csv| std::fs::write(&path, csv).map_err(anyhow::Error::from));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported assignment matrix");
+                format!("Assignment matrix exported: {}", path.display())
+            }
+            Err(err) => format!("Assignment matrix export failed: {err:#}"),
+        };
+    }
+
+    /// Legend for the overlay header; token fills use the same `OverlayStatus::color`.
+    fn overlay_status_legend(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            for status in OverlayStatus::iter() {
+                let count = self
+                    .project
+                    .overlay_nodes
+                    .iter()
+                    .filter(|n| n.page == self.overlay_page && n.status == status)
+                    .count();
+                ui.label(RichText::new("●").color(status.color()));
+                ui.small(format!("{} ({count})", status.label()));
+            }
+        });
+    }
+
+    /// Captures calibration clicks in page space. Returns `true` when the click was
+    /// consumed so placement and routing tools ignore it.
+    fn overlay_calibration_input(&mut self, response: &egui::Response, canvas_min: egui::Pos2) -> bool {
+        if !self.calibration_active {
+            return false;
+        }
+        if response.clicked_by(egui::PointerButton::Primary) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                if self.calibration_points.len() == 2 {
+                    self.calibration_points.clear();
+                }
+                self.calibration_points
+                    .push(overlay_to_page(canvas_min, self.overlay_pan, self.overlay_zoom, pos));
+            }
+        }
+        true
+    }
+
+    fn overlay_calibration_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .selectable_label(self.calibration_active, "📏 Calibrate scale")
+                .on_hover_text("Click two points on the drawing, then enter their real distance")
+                .clicked()
+            {
+                self.calibration_active = !self.calibration_active;
+                self.calibration_points.clear();
+            }
+            if self.calibration_active {
+                match self.calibration_points.as_slice() {
+                    [a, b] => {
+                        let pixels = egui::pos2(a[0], a[1]).distance(egui::pos2(b[0], b[1]));
+                        ui.label(format!("{pixels:.0} px ="));
+                        ui.add(egui::DragValue::new(&mut self.calibration_distance).speed(0.5).range(0.01..=f32::MAX));
+                        ui.add(egui::TextEdit::singleline(&mut self.calibration_unit).desired_width(40.0));
+                        if ui.add_enabled(pixels > 1.0, egui::Button::new("Apply")).clicked() {
+                            self.project.drawing_scale = Some(DrawingScale {
+                                units_per_pixel: self.calibration_distance / pixels,
+                                unit: self.calibration_unit.trim().to_string(),
+                            });
+                            self.calibration_active = false;
+                            self.calibration_points.clear();
+                            self.mark_dirty();
+                            self.status = "Drawing scale calibrated".to_string();
+                        }
+                    }
+                    points => {
+                        ui.small(format!("Pick point {} of 2", points.len() + 1));
+                    }
+                }
+            }
+            ui.separator();
+            ui.label(self.wire_length_label());
+        });
+    }
+
+    fn wire_length_label(&self) -> String {
+        let pixels: f32 = self
+            .project
+            .overlay_lines
+            .iter()
+            .map(|l| egui::pos2(l.from[0], l.from[1]).distance(egui::pos2(l.to[0], l.to[1])))
+            .sum();
+        match &self.project.drawing_scale {
+            Some(scale) => format!("Wire total: {:.1} {}", pixels * scale.units_per_pixel, scale.unit),
+            None => format!("Wire total: {pixels:.0} px (uncalibrated)"),
+        }
+    }
+
+    /// Job factor and overhead sliders at the top of the Hours Adjustments popup. The
+    /// multipliers are neutral at 1.0, the QA/PM/risk percentages at 0.
+    fn estimator_factor_sliders(&mut self, ui: &mut Ui, estimate: &HoursEstimate) -> bool {
+        use FactorEffect::{OverheadPercent, RowMultiplier};
+        let mut changed = false;
+        ui.label(RichText::new("Job factors").strong());
+        changed |= self.estimator_factor_slider(ui, estimate, "Complexity", 0.5..=2.0, RowMultiplier, |e| {
+            &mut e.complexity_factor
+        });
+        changed |= self.estimator_factor_slider(ui, estimate, "Renovation", 0.5..=2.0, RowMultiplier, |e| {
+            &mut e.renovation_factor
+        });
+        changed |= self.estimator_factor_slider(ui, estimate, "Integration", 0.5..=2.0, RowMultiplier, |e| {
+            &mut e.integration_factor
+        });
+        ui.separator();
+        ui.label(RichText::new("Overhead (% of subtotal)").strong());
+        changed |= self.estimator_factor_slider(ui, estimate, "QA %", 0.0..=30.0, OverheadPercent, |e| {
+            &mut e.qa_percent
+        });
+        changed |= self.estimator_factor_slider(ui, estimate, "PM %", 0.0..=30.0, OverheadPercent, |e| {
+            &mut e.pm_percent
+        });
+        changed |= self.estimator_factor_slider(ui, estimate, "Risk %", 0.0..=30.0, OverheadPercent, |e| {
+            &mut e.risk_percent
+        });
+        changed
+    }
+
+    /// Per-kind point weights below the job factors in the Hours Adjustments popup.
+    fn point_kind_weight_sliders(&mut self, ui: &mut Ui, estimate: &HoursEstimate) -> bool {
+        let mut changed = false;
+        ui.label(RichText::new("Point kind weighting").strong());
+        for kind in POINT_KINDS {
+            changed |= self.estimator_factor_slider(
+                ui,
+                estimate,
+                &format!("{} {kind:?}", kind.icon()),
+                0.25..=4.0,
+                FactorEffect::PointWeight(kind),
+                move |e| e.point_kind_weights.weight_mut(kind),
+            );
+        }
+        changed
+    }
+
+    /// Hours a factor at `value` adds to `estimate`'s total compared with its neutral
+    /// value (negative when it removes hours). The model is multiplicative, so each
+    /// effect follows from the one estimate: a multiplier contributes `hours - hours /
+    /// value` of what it scales, grossed up by the overhead percentage.
+    fn factor_marginal_hours(&self, estimate: &HoursEstimate, effect: FactorEffect, value: f32) -> f32 {
+        let neutral = effect.neutral();
+        if (value - neutral).abs() < f32::EPSILON {
+            return 0.0;
+        }
+        let subtotal: f32 = estimate.totals[..4].iter().sum();
+        let gross_up = if subtotal > 0.0 { estimate.totals[5] / subtotal } else { 1.0 };
+        let scaled = |hours: f32| (hours - hours / value.max(f32::EPSILON) * neutral) * gross_up;
+        match effect {
+            FactorEffect::RowMultiplier => scaled(estimate.rows.iter().flat_map(|r| r.hours).sum()),
+            FactorEffect::OverheadPercent => subtotal * value / 100.0,
+            FactorEffect::PointWeight(kind) => {
+                let idx = POINT_KINDS.iter().position(|&k| k == kind).unwrap_or_default();
+                scaled(estimate.kind_hours[idx])
+            }
+        }
+    }
+
+    /// One Hours Adjustments slider followed by the hours its value adds or removes
+    /// versus neutral, per `factor_marginal_hours`. `estimate` is taken once per frame
+    /// for the whole popup, so the readout is read before the slider moves. Returns
+    /// whether the value changed.
+    fn estimator_factor_slider(
+        &mut self,
+        ui: &mut Ui,
+        estimate: &HoursEstimate,
+        label: &str,
+        range: std::ops::RangeInclusive<f32>,
+        effect: FactorEffect,
+        field: impl Fn(&mut EstimatorSettings) -> &mut f32,
+    ) -> bool {
+        let neutral = effect.neutral();
+        let delta = self.factor_marginal_hours(estimate, effect, *field(&mut self.project.estimator));
+        ui.horizontal(|ui| {
+            let changed = ui
+                .add(
+                    egui::Slider::new(field(&mut self.project.estimator), range)
+                        .text(label)
+                        .fixed_decimals(2),
+                )
+                .changed();
+            if changed {
+                self.mark_dirty();
+            }
+            let text = RichText::new(format!("{delta:+.1} h")).monospace();
+            let text = match delta {
+                d if d > 0.05 => text.color(Color32::from_rgb(230, 150, 60)),
+                d if d < -0.05 => text.color(Color32::from_rgb(80, 180, 120)),
+                _ => text.weak(),
+            };
+            ui.label(text)
+                .on_hover_text(format!("Change to the total versus a neutral {label} of {neutral}"));
+            changed
+        })
+        .inner
+    }
+
+    /// (label, hours, dollars) per category plus the grand total, from `estimate_hours`.
+    fn labor_cost_rows(&self) -> Vec<(&'static str, f32, f32)> {
+        let [eng, gfx, cx, custom, overhead, total] = self.estimate_hours().totals;
+        let rates = &self.project.estimator.labor_rates;
+        let rows = [
+            ("Engineering", eng, rates.engineering()),
+            ("Graphics", gfx, rates.graphics()),
+            ("Commissioning", cx, rates.commissioning()),
+            ("Other custom", custom, rates.blended),
+            ("QA / PM / Risk", overhead, rates.blended),
+        ];
+        let mut out: Vec<_> = rows
+            .iter()
+            .map(|&(label, hours, rate)| (label, hours, hours * rate))
+            .collect();
+        let dollars = out.iter().map(|r| r.2).sum();
+        out.push(("Total", total, dollars));
+        out
+    }
+
+    fn labor_cost_card(&mut self, ui: &mut Ui) {
+        let rows = self.labor_cost_rows();
+        let labor_total = rows.last().map(|r| r.2).unwrap_or_default();
+        let mut changed = false;
+        Self::card_frame().show(ui, |ui| {
+            ui.label(RichText::new("Cost").strong());
+            ui.separator();
+            let rates = &mut self.project.estimator.labor_rates;
+            ui.horizontal(|ui| {
+                ui.label("Labor rate ($/hr)");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut rates.blended).speed(1.0).range(0.0..=1000.0))
+                    .changed();
+            });
+            egui::Grid::new("labor_cost_grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Category");
+                    ui.strong("Hours");
+                    ui.strong("Cost");
+                    ui.end_row();
+                    for (label, hours, dollars) in &rows {
+                        ui.label(*label);
+                        ui.monospace(format!("{hours:.1}"));
+                        ui.monospace(format_currency(*dollars));
+                        ui.end_row();
+                    }
+                });
+        });
+        if changed {
+            self.mark_dirty();
+        }
+        ui.add_space(8.0);
+        self.commercial_terms_card(ui, labor_total);
+    }
+
+    /// Cost section appended to the proposal by `export_proposal_markdown`.
+    fn cost_markdown(&self) -> String {
+        let rows = self.labor_cost_rows();
+        let mut out = String::from("## Labor Cost\n\n| Category | Hours | Cost |\n|---|---:|---:|\n");
+        for (label, hours, dollars) in &rows {
+            out.push_str(&format!("| {label} | {hours:.1} | {} |\n", format_currency(*dollars)));
+        }
+        let labor_total = rows.last().map(|r| r.2).unwrap_or_default();
+        let summary = CostSummary::new(labor_total, &self.project.commercial);
+        out.push('\n');
+        out.push_str(&cost_summary_markdown(&summary, &self.project.commercial));
+        if !self.project.expenses.is_empty() {
+            out.push('\n');
+            out.push_str(&expenses_markdown(&self.project.expenses));
+        }
+        let bom = bill_of_materials(&self.project.objects);
+        if !bom.is_empty() {
+            out.push('\n');
+            out.push_str(&bom_markdown(&bom));
+        }
+        out
+    }
+
+    /// Add/rename groups and edit key/value rows on the selected object.
+    fn property_groups_editor(&mut self, ui: &mut Ui, id: u64) {
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) else {
+            return;
+        };
+        if Self::property_groups_fields(ui, ("property_groups", id), "🗂 Property Groups", &mut obj.property_groups) {
+            self.mark_dirty();
+        }
+    }
+
+    /// Group and key/value row editor shared by objects and template points. Returns
+    /// `true` when anything changed.
+    fn property_groups_fields(
+        ui: &mut Ui,
+        id_source: impl std::hash::Hash,
+        title: &str,
+        groups: &mut Vec<PropertyGroup>,
+    ) -> bool {
+        let mut changed = false;
+        let mut remove_group = None;
+        egui::CollapsingHeader::new(title)
+            .id_source(id_source)
+            .default_open(!groups.is_empty())
+            .show(ui, |ui| {
+                for (g_idx, group) in groups.iter_mut().enumerate() {
+                    ui.push_id(g_idx, |ui| {
+                        ui.horizontal(|ui| {
+                            changed |= ui
+                                .add(egui::TextEdit::singleline(&mut group.name).hint_text("Group name"))
+                                .changed();
+                            if ui.small_button("🗑").on_hover_text("Remove group").clicked() {
+                                remove_group = Some(g_idx);
+                            }
+                        });
+                        let mut remove_row = None;
+                        egui::Grid::new("property_rows").num_columns(3).show(ui, |ui| {
+                            for (r_idx, item) in group.items.iter_mut().enumerate() {
+                                changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut item.key).hint_text("Key").desired_width(110.0))
+                                    .changed();
+                                changed |= ui
+                                    .add(egui::TextEdit::singleline(&mut item.value).hint_text("Value").desired_width(140.0))
+                                    .changed();
+                                if ui.small_button("✖").clicked() {
+                                    remove_row = Some(r_idx);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                        if let Some(r_idx) = remove_row {
+                            group.items.remove(r_idx);
+                            changed = true;
+                        }
+                        if ui.small_button("➕ Row").clicked() {
+                            group.items.push(PropertyItem::default());
+                            changed = true;
+                        }
+                        ui.separator();
+                    });
+                }
+                if ui.button("➕ Add Group").clicked() {
+                    groups.push(PropertyGroup {
+                        name: "New Group".to_string(),
+                        items: Vec::new(),
+                    });
+                    changed = true;
+                }
+            });
+        if let Some(g_idx) = remove_group {
+            groups.remove(g_idx);
+            changed = true;
+        }
+        changed
+    }
+
+    /// Assigns `template_name` to every equipment of the template's type and re-syncs it.
+    /// Equipment with `equipment_type_override` set is left alone.
+    fn apply_template_to_matching(&mut self, template_name: &str) -> usize {
+        let Some(equipment_type) = self
+            .user_templates
+            .iter()
+            .find(|t| t.name == template_name)
+            .map(|t| t.equipment_type.clone())
+        else {
+            return 0;
+        };
+        let ids: Vec<u64> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| {
+                o.object_type == ObjectType::Equipment
+                    && !o.equipment_type_override
+                    && o.equipment_type.trim().eq_ignore_ascii_case(equipment_type.trim())
+            })
+            .map(|o| o.id)
+            .collect();
+        for &id in &ids {
+            if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) {
+                obj.template_name = template_name.to_string();
+            }
+            self.sync_equipment_from_template(id);
+        }
+        if !ids.is_empty() {
+            self.mark_dirty();
+        }
+        ids.len()
+    }
+
+    fn template_apply_all_button(&mut self, ui: &mut Ui, template_name: &str) {
+        if ui
+            .button("⇉ Apply to all matching equipment")
+            .on_hover_text("Assign this template to every equipment of the same type (overrides are skipped)")
+            .clicked()
+        {
+            let updated = self.apply_template_to_matching(template_name);
+            self.status = format!("{template_name} applied to {updated} equipment");
+        }
+    }
+
+    /// Tree rows in display order, skipping children of `collapsed_tree_nodes` and rows
+    /// hidden by the search and type filter.
+    fn visible_tree_order(&self) -> Vec<u64> {
+        fn walk(app: &AutoMateApp, id: u64, ancestors: &HashSet<u64>, out: &mut Vec<u64>) {
+            out.push(id);
+            if app.collapsed_tree_nodes.contains(&id) {
+                return;
+            }
+            for child in app
+                .project
+                .objects
+                .iter()
+                .filter(|o| o.parent_id == Some(id) && app.tree_row_visible(o, ancestors))
+            {
+                walk(app, child.id, ancestors, out);
+            }
+        }
+        let ancestors = self.search_match_ancestors();
+        let mut out = Vec::new();
+        for root in self.filtered_root_ids() {
+            walk(self, root, &ancestors, &mut out);
+        }
+        out
+    }
+
+    /// Click handling for a tree row: plain click selects one, Ctrl/Cmd toggles,
+    /// Shift extends from the last anchor across the visible rows.
+    fn handle_tree_click(&mut self, id: u64, modifiers: egui::Modifiers) {
+        if modifiers.shift {
+            if let Some(anchor) = self.tree_anchor {
+                let order = self.visible_tree_order();
+                let a = order.iter().position(|&x| x == anchor);
+                let b = order.iter().position(|&x| x == id);
+                if let (Some(a), Some(b)) = (a, b) {
+                    self.selected_objects.extend(&order[a.min(b)..=a.max(b)]);
+                    self.selected_object = Some(id);
+                    return;
+                }
+            }
+        }
+        if modifiers.command {
+            if !self.selected_objects.remove(&id) {
+                self.selected_objects.insert(id);
+            }
+        } else {
+            self.selected_objects.clear();
+            self.selected_objects.insert(id);
+        }
+        self.selected_object = Some(id);
+        self.tree_anchor = Some(id);
+    }
+
+    fn delete_selected_objects(&mut self) {
+        let ids: Vec<u64> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| self.selected_objects.contains(&o.id) && o.object_type != ObjectType::Building)
+            .map(|o| o.id)
+            .collect();
+        if !ids.is_empty() {
+            self.push_project_history();
+        }
+        let mut removed = 0;
+        for id in ids {
+            // An earlier subtree removal may already have taken this one.
+            if self.project.objects.iter().any(|o| o.id == id) {
+                self.remove_object_subtree(id);
+                removed += 1;
+            }
+        }
+        self.selected_objects.clear();
+        if self
+            .selected_object
+            .is_some_and(|id| !self.project.objects.iter().any(|o| o.id == id))
+        {
+            self.selected_object = self
+                .project
+                .objects
+                .iter()
+                .find(|o| o.object_type == ObjectType::Building)
+                .map(|o| o.id);
+        }
+        if removed > 0 {
+            self.mark_dirty();
+        }
+        self.status = format!("Deleted {removed} object(s)");
+    }
+
+    fn move_selected_objects(&mut self, new_parent: u64) {
+        let Some(parent_type) = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == new_parent)
+            .map(|o| o.object_type)
+        else {
+            return;
+        };
+        let ids: Vec<u64> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| {
+                self.selected_objects.contains(&o.id)
+                    && o.id != new_parent
+                    && Self::can_reparent_object(o.object_type, parent_type)
+            })
+            .map(|o| o.id)
+            .collect();
+        if !ids.is_empty() {
+            self.push_project_history();
+        }
+        for &id in &ids {
+            self.reparent_object(id, new_parent);
+        }
+        if !ids.is_empty() {
+            self.mark_dirty();
+        }
+        self.status = format!(
+            "Moved {} of {} selected object(s)",
+            ids.len(),
+            self.selected_objects.len()
+        );
+    }
+
+    /// Bulk toolbar above the tree; only shown while more than one row is selected.
+    fn tree_selection_toolbar(&mut self, ui: &mut Ui) {
+        if self.selected_objects.len() < 2 {
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{} selected", self.selected_objects.len()));
+            if ui.button("🗑 Delete selected").clicked() {
+                self.delete_selected_objects();
+            }
+            let target_name = self
+                .bulk_move_target
+                .and_then(|id| self.project.objects.iter().find(|o| o.id == id))
+                .map(|o| o.name.clone())
+                .unwrap_or_else(|| "Move selected under…".to_string());
+            egui::ComboBox::from_id_source("bulk_move_target")
+                .selected_text(target_name)
+                .show_ui(ui, |ui| {
+                    for parent in self
+                        .project
+                        .objects
+                        .iter()
+                        .filter(|o| o.object_type != ObjectType::Point)
+                    {
+                        ui.selectable_value(
+                            &mut self.bulk_move_target,
+                            Some(parent.id),
+                            format!("{} {}", parent.object_type.icon(), parent.name),
+                        );
+                    }
+                });
+            if let Some(target) = self.bulk_move_target {
+                if ui.button("Move").clicked() {
+                    self.move_selected_objects(target);
+                    self.bulk_move_target = None;
+                }
+            }
+            if ui.button("Clear").clicked() {
+                self.selected_objects.clear();
+            }
+        });
+    }
+
+    /// Replaces the single-object editor in `right_properties` when several are selected.
+    /// Returns `true` if the summary was shown.
+    fn multi_selection_summary(&self, ui: &mut Ui) -> bool {
+        if self.selected_objects.len() < 2 {
+            return false;
+        }
+        ui.heading(format!("{} objects selected", self.selected_objects.len()));
+        for kind in ObjectType::iter() {
+            let count = self
+                .project
+                .objects
+                .iter()
+                .filter(|o| o.object_type == kind && self.selected_objects.contains(&o.id))
+                .count();
+            if count > 0 {
+                ui.label(format!("{} {kind:?}: {count}", kind.icon()));
+            }
+        }
+        ui.small("Use the tree toolbar to delete or move the selection.");
+        true
+    }
+
+    /// Type the `object_node` "Add …" context entry creates under `parent`.
+    fn child_object_type(parent: ObjectType) -> Option<ObjectType> {
+        match parent {
+            ObjectType::Building => Some(ObjectType::Controller),
+            ObjectType::Controller => Some(ObjectType::Equipment),
+            ObjectType::Equipment => Some(ObjectType::Point),
+            ObjectType::Point => None,
+        }
+    }
+
+    /// Parent/child edges the tree accepts for drag-and-drop and bulk moves.
+    fn can_reparent_object(child: ObjectType, parent: ObjectType) -> bool {
+        matches!(
+            (child, parent),
+            (ObjectType::Controller, ObjectType::Building)
+                | (ObjectType::Equipment, ObjectType::Controller)
+                | (ObjectType::Point, ObjectType::Equipment)
+        )
+    }
+
+    /// Drop handling for a tree row in `object_node`; rows are dragged with a `u64` id payload.
+    fn tree_drop_target(&mut self, response: &egui::Response, target_id: u64) {
+        let Some(target_type) = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == target_id)
+            .map(|o| o.object_type)
+        else {
+            return;
+        };
+        let hovered = response.dnd_hover_payload::<u64>().and_then(|id| {
+            self.project
+                .objects
+                .iter()
+                .find(|o| o.id == *id)
+                .map(|o| o.object_type)
+        });
+        if let Some(child_type) = hovered {
+            let stroke = if Self::can_reparent_object(child_type, target_type) {
+                egui::Stroke::new(1.5, self.accent())
+            } else {
+                egui::Stroke::new(1.5, Color32::from_rgb(200, 70, 70))
+            };
+            response
+                .ctx
+                .layer_painter(response.layer_id)
+                .rect_stroke(response.rect, 4.0, stroke);
+        }
+        let Some(dragged) = response.dnd_release_payload::<u64>() else {
+            return;
+        };
+        let dragged = *dragged;
+        let Some(child) = self.project.objects.iter().find(|o| o.id == dragged) else {
+            return;
+        };
+        if dragged == target_id || child.parent_id == Some(target_id) {
+            return;
+        }
+        if Self::can_reparent_object(child.object_type, target_type) {
+            let name = child.name.clone();
+            self.push_project_history();
+            self.reparent_object(dragged, target_id);
+            self.mark_dirty();
+            self.status = format!("Moved {name}");
+        } else {
+            self.status = format!(
+                "A {:?} can't be placed under a {:?}",
+                child.object_type, target_type
+            );
+        }
+    }
+
+    /// True while the text search or the type filter narrows the tree.
+    fn tree_filter_active(&self) -> bool {
+        !self.object_search_query.trim().is_empty() || self.tree_type_filter.is_active()
+    }
+
+    /// Whether `obj` matches both the text search and the type filter.
+    fn tree_row_matches(&self, obj: &BasObject) -> bool {
+        let query = self.object_search_query.trim();
+        (query.is_empty() || object_matches_query(obj, query)) && self.tree_type_filter.matches(obj)
+    }
+
+    /// Rows `filtered_root_ids` and `object_node` keep while a filter is active: the
+    /// matches themselves plus their ancestors (`ancestors` from
+    /// `search_match_ancestors`), so hits are always reachable.
+    fn tree_row_visible(&self, obj: &BasObject, ancestors: &HashSet<u64>) -> bool {
+        !self.tree_filter_active() || self.tree_row_matches(obj) || ancestors.contains(&obj.id)
+    }
+
+    /// Ids of every ancestor of an object matching the tree search and type filter.
+    fn search_match_ancestors(&self) -> HashSet<u64> {
+        let mut out = HashSet::new();
+        if !self.tree_filter_active() {
+            return out;
+        }
+        for obj in self.project.objects.iter().filter(|o| self.tree_row_matches(o)) {
+            let mut parent = obj.parent_id;
+            while let Some(id) = parent {
+                if !out.insert(id) {
+                    break;
+                }
+                parent = self.project.objects.iter().find(|o| o.id == id).and_then(|o| o.parent_id);
+            }
+        }
+        out
+    }
+
+    fn collapse_all_tree_nodes(&mut self) {
+        // Branches leading to search hits stay open so filtered results remain visible.
+        let keep_open = self.search_match_ancestors();
+        let parents: HashSet<u64> = self.project.objects.iter().filter_map(|o| o.parent_id).collect();
+        self.collapsed_tree_nodes = parents.difference(&keep_open).copied().collect();
+    }
+
+    fn tree_collapse_controls(&mut self, ui: &mut Ui) {
+        if ui.small_button("⊟").on_hover_text("Collapse all").clicked() {
+            self.collapse_all_tree_nodes();
+        }
+        if ui.small_button("⊞").on_hover_text("Expand all").clicked() {
+            self.collapsed_tree_nodes.clear();
+        }
+    }
+
+    /// Vector index of the previous (`up`) or next sibling sharing `id`'s parent.
+    fn sibling_swap_index(&self, id: u64, up: bool) -> Option<(usize, usize)> {
+        let objects = &self.project.objects;
+        let idx = objects.iter().position(|o| o.id == id)?;
+        let parent = objects[idx].parent_id;
+        let other = if up {
+            objects[..idx].iter().rposition(|o| o.parent_id == parent)
+        } else {
+            objects[idx + 1..]
+                .iter()
+                .position(|o| o.parent_id == parent)
+                .map(|offset| idx + 1 + offset)
+        }?;
+        Some((idx, other))
+    }
+
+    fn move_object_among_siblings(&mut self, id: u64, up: bool) {
+        if let Some((a, b)) = self.sibling_swap_index(id, up) {
+            self.push_project_history();
+            self.project.objects.swap(a, b);
+            self.mark_dirty();
+        }
+    }
+
+    /// "Move Up"/"Move Down" entries for the `object_node` context menu.
+    fn reorder_menu_items(&mut self, ui: &mut Ui, id: u64) {
+        let can_up = self.sibling_swap_index(id, true).is_some();
+        let can_down = self.sibling_swap_index(id, false).is_some();
+        if ui.add_enabled(can_up, egui::Button::new("⬆ Move Up")).clicked() {
+            self.move_object_among_siblings(id, true);
+            ui.close_menu();
+        }
+        if ui.add_enabled(can_down, egui::Button::new("⬇ Move Down")).clicked() {
+            self.move_object_among_siblings(id, false);
+            ui.close_menu();
+        }
+    }
+
+    /// Points under a controller, counted through its equipment children.
+    fn controller_point_count(&self, controller_id: u64) -> usize {
+        let equipment: HashSet<u64> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Equipment && o.parent_id == Some(controller_id))
+            .map(|o| o.id)
+            .collect();
+        self.project
+            .objects
+            .iter()
+            .filter(|o| {
+                o.object_type == ObjectType::Point
+                    && o.parent_id.is_some_and(|p| equipment.contains(&p))
+            })
+            .count()
+    }
+
+    /// Controllers whose point count exceeds their license cap, for `ux_health_issues`.
+    fn license_capacity_issues(&self) -> Vec<String> {
+        self.project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Controller)
+            .filter_map(|c| {
+                let cap = license_point_cap(&c.controller_license)?;
+                let points = self.controller_point_count(c.id);
+                (points > cap).then(|| {
+                    format!(
+                        "{} has {points} points but its {} license allows {cap}",
+                        c.name, c.controller_license
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Controllers carrying more than twice the average point load, or more than the
+    /// configured threshold, for `ux_health_issues`.
+    fn controller_loading_issues(&self) -> Vec<String> {
+        let loads: Vec<(&str, usize)> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Controller)
+            .map(|c| (c.name.as_str(), self.controller_point_count(c.id)))
+            .collect();
+        let threshold = self.preferences.controller_point_threshold as usize;
+        // One controller has nothing to be unbalanced against.
+        let average = (loads.len() > 1)
+            .then(|| loads.iter().map(|(_, n)| *n).sum::<usize>() as f32 / loads.len() as f32);
+        loads
+            .iter()
+            .filter_map(|&(name, points)| {
+                if threshold > 0 && points > threshold {
+                    Some(format!("{name} has {points} points, above the {threshold}-point controller limit"))
+                } else {
+                    let average = average.filter(|avg| *avg > 0.0 && points as f32 > 2.0 * avg)?;
+                    Some(format!(
+                        "{name} has {points} points, more than twice the {average:.0}-point controller average"
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Capacity readout for a controller in `right_properties`, red when over the cap.
+    fn controller_license_badge(&self, ui: &mut Ui, controller_id: u64) {
+        let Some(controller) = self.project.objects.iter().find(|o| o.id == controller_id) else {
+            return;
+        };
+        let points = self.controller_point_count(controller_id);
+        match license_point_cap(&controller.controller_license) {
+            Some(cap) if points > cap => {
+                ui.label(
+                    RichText::new(format!("⛔ {points}/{cap} points — over license capacity"))
+                        .color(Color32::from_rgb(230, 80, 80))
+                        .strong(),
+                );
+            }
+            Some(cap) => {
+                ui.small(format!("{points}/{cap} licensed points"));
+            }
+            None => {
+                ui.small(format!("{points} points (no license cap)"));
+            }
+        }
+    }
+
+    /// I/O breakdown row for `workspace_header`.
+    fn point_mix_header(&self, ui: &mut Ui) {
+        let counts = point_kind_counts(&self.project.objects);
+        let total: usize = counts.iter().map(|(_, n)| n).sum();
+        ui.horizontal_wrapped(|ui| {
+            for (kind, count) in counts {
+                ui.label(format!("{} {kind:?} {count}", kind.icon()))
+                    .on_hover_text(format!("{count} {kind:?} point(s)"));
+            }
+            ui.separator();
+            ui.label(RichText::new(format!("I/O total {total}")).strong());
+        });
+    }
+
+    /// Interval-gated autosave of unsaved changes. Serialization happens here, but
+    /// zipping, obfuscation, and the disk write run on a worker so large embedded PDFs
+    /// don't stall the UI. `poll_autosave` restarts the interval once a write succeeds.
+    fn autosave_project_in_background(&mut self) {
+        if !self.dirty || self.autosave_rx.is_some() {
+            return;
+        }
+        let interval = Duration::from_secs(u64::from(self.preferences.autosave_minutes.max(1)) * 60);
+        if self
+            .last_autosave_at
+            .is_some_and(|at| at.elapsed() < interval)
+            || self.autosave_retry_at.is_some_and(|at| Instant::now() < at)
+        {
+            return;
+        }
+        self.stash_active_drawing();
+        let payload = match serde_json::to_vec_pretty(&self.project) {
+            Ok(payload) => payload,
+            Err(err) => {
+                self.status = format!("Autosave failed: {err}");
+                self.schedule_autosave_retry();
+                return;
+            }
+        };
+        let path = self
+            .project_path
+            .clone()
+            .unwrap_or_else(|| self.autosave_fallback_path());
+        let mut assets = self.drawing_assets();
+        if let Some(bytes) = &self.overview_image_bytes {
+            assets.push(("assets/overview.png".to_string(), bytes.clone()));
+        }
+        let project_uuid = self.project.project_uuid;
+        let generations = self.preferences.autosave_generations;
+        let (tx, rx) = mpsc::channel();
+        self.autosave_rx = Some(rx);
+        std::thread::spawn(move || {
+            let result = write_m8_bundle(&path, &payload, &assets)
+                .and_then(|()| Ok(keep_autosave_generation(&path, project_uuid, generations)?))
+                .map(|()| path)
+                .map_err(|err| err.to_string());
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_autosave(&mut self) {
+        let Some(rx) = &self.autosave_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(path)) => {
+                tracing::debug!(path = %path.display(), "autosave complete");
+                self.status = format!("Autosaved {}", path.display());
+                self.last_autosave_at = Some(Instant::now());
+                self.autosave_retry_at = None;
+                self.autosave_rx = None;
+            }
+            Ok(Err(err)) => {
+                self.status = format!("Autosave failed: {err}");
+                self.schedule_autosave_retry();
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.schedule_autosave_retry(),
+        }
+    }
+
+    /// A failed write waits a full interval before trying again, without touching
+    /// `last_autosave_at`, so the status bar keeps reporting the last good autosave.
+    fn schedule_autosave_retry(&mut self) {
+        let interval = Duration::from_secs(u64::from(self.preferences.autosave_minutes.max(1)) * 60);
+        self.autosave_retry_at = Some(Instant::now() + interval);
+        self.autosave_rx = None;
+    }
+
+    /// Starts rendering `overlay_page` on a worker thread; the texture is uploaded by
+    /// `poll_overlay_render`. A newer request supersedes any render still in flight.
+    fn request_overlay_render(&mut self) {
+        let Some(bytes) = self.overlay_pdf_bytes.clone() else {
+            self.overlay_texture = None;
+            self.overlay_render_rx = None;
+            return;
+        };
+        self.overlay_render_generation += 1;
+        let generation = self.overlay_render_generation;
+        let page_index = self.overlay_page;
+        let render_scale = self.preferences.pdf_render_scale;
+        let form_data = self.preferences.render_pdf_form_data;
+        let (tx, rx) = mpsc::channel();
+        self.overlay_render_rx = Some(rx);
+        std::thread::spawn(move || {
+            let (page_count, scale, image) = match render_pdf_page(&bytes, page_index, render_scale, form_data) {
+                Ok((count, scale, image)) => (count, scale, Ok(image)),
+                Err(err) => (0, 1.0, Err(format!("{err:#}"))),
+            };
+            let _ = tx.send(OverlayRender {
+                generation,
+                page_count,
+                scale,
+                image,
+            });
+        });
+    }
+
+    fn poll_overlay_render(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.overlay_render_rx else {
+            return;
+        };
+        let Ok(render) = rx.try_recv() else {
+            return;
+        };
+        self.overlay_render_rx = None;
+        // The page may have changed while this one rendered; drop stale results.
+        if render.generation != self.overlay_render_generation {
+            return;
+        }
+        match render.image {
+            Ok(image) => {
+                self.overlay_page_count = render.page_count;
+                self.overlay_texture_scale = render.scale;
+                self.overlay_texture =
+                    Some(ctx.load_texture("overlay_pdf", image, egui::TextureOptions::LINEAR));
+            }
+            Err(err) => self.status = format!("Drawing render failed: {err}"),
+        }
+    }
+
+    fn overlay_rendering(&self) -> bool {
+        self.overlay_render_rx.is_some()
+    }
+
+    /// "Rendering…" placeholder drawn over the canvas while a page is in flight.
+    fn overlay_render_indicator(&self, ui: &mut Ui, canvas: egui::Rect) {
+        if !self.overlay_rendering() {
+            return;
+        }
+        ui.painter().text(
+            canvas.center(),
+            egui::Align2::CENTER_CENTER,
+            format!("Rendering page {}…", self.overlay_page + 1),
+            egui::FontId::proportional(18.0),
+            Color32::from_rgb(200, 210, 225),
+        );
+        ui.ctx().request_repaint();
+    }
+
+    fn recovery_dialog(&mut self, ctx: &egui::Context) {
+        if self.recovery_candidates.is_empty() {
+            return;
+        }
+        // One entry per project, newest first; older generations nest underneath.
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (idx, candidate) in self.recovery_candidates.iter().enumerate() {
+            let existing = candidate.project_uuid.and_then(|uuid| {
+                groups
+                    .iter()
+                    .position(|group| self.recovery_candidates[group[0]].project_uuid == Some(uuid))
+            });
+            match existing {
+                Some(group) => groups[group].push(idx),
+                None => groups.push(vec![idx]),
+            }
+        }
+        let mut load = None;
+        let mut delete = Vec::new();
+        let mut dismiss = false;
+        egui::Window::new("Recover Autosave")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("AutoMate found autosaved work newer than the last save.");
+                ui.add_space(6.0);
+                for group in &groups {
+                    let newest = &self.recovery_candidates[group[0]];
+                    let current = newest.project_uuid == Some(self.project.project_uuid);
+                    ui.horizontal(|ui| {
+                        let title = match newest.project_uuid {
+                            Some(uuid) => format!("Project {}", &uuid.to_string()[..8]),
+                            None => newest
+                                .path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().into_owned())
+                                .unwrap_or_default(),
+                        };
+                        ui.label(format!(
+                            "{title} — {}{}",
+                            recovery_timestamp(newest),
+                            if current { " (this project)" } else { "" }
+                        ));
+                        if ui.button("Load latest").clicked() {
+                            load = Some(group[0]);
+                        }
+                        if ui
+                            .button("🗑")
+                            .on_hover_text("Delete every autosave of this project")
+                            .clicked()
+                        {
+                            delete.extend(group.iter().copied());
+                        }
+                    });
+                    if group.len() < 2 {
+                        continue;
+                    }
+                    egui::CollapsingHeader::new(format!("{} earlier generation(s)", group.len() - 1))
+                        .id_source(("recovery_generations", group[0]))
+                        .show(ui, |ui| {
+                            for &idx in &group[1..] {
+                                ui.horizontal(|ui| {
+                                    ui.label(recovery_timestamp(&self.recovery_candidates[idx]));
+                                    if ui.button("Load").clicked() {
+                                        load = Some(idx);
+                                    }
+                                    if ui.small_button("🗑").on_hover_text("Delete this autosave").clicked() {
+                                        delete.push(idx);
+                                    }
+                                });
+                            }
+                        });
+                }
+                ui.separator();
+                if ui.button("Dismiss").clicked() {
+                    dismiss = true;
+                }
+            });
+        if let Some(idx) = load {
+            let candidate = self.recovery_candidates.remove(idx);
+            match self.load_project_from_path(&candidate.path) {
+                Ok(()) => {
+                    // Recovered work has no real file yet; the next Save should prompt.
+                    self.project_path = None;
+                    self.mark_dirty();
+                    self.status = format!("Recovered {}", self.project.name);
+                    self.recovery_candidates.clear();
+                }
+                Err(err) => self.status = format!("Recovery failed: {err}"),
+            }
+        } else if !delete.is_empty() {
+            delete.sort_unstable_by(|a, b| b.cmp(a));
+            for idx in delete {
+                let candidate = self.recovery_candidates.remove(idx);
+                if let Err(err) = std::fs::remove_file(&candidate.path) {
+                    self.status = format!("Could not delete autosave: {err}");
+                }
+            }
+        } else if dismiss {
+            self.recovery_candidates.clear();
+        }
+    }
+
+    fn accent(&self) -> Color32 {
+        let [r, g, b] = self.preferences.accent_color;
+        Color32::from_rgb(r, g, b)
+    }
+
+    /// Preference controls for the Settings dialog; changes are written immediately.
+    fn preferences_editor(&mut self, ui: &mut Ui) {
+        let prefs = &mut self.preferences;
+        let mut changed = false;
+        let mut rerender = false;
+        egui::Grid::new("app_preferences_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("UI scale");
+                changed |= ui.add(egui::Slider::new(&mut prefs.ui_scale, 0.75..=2.0).fixed_decimals(2)).changed();
+                ui.end_row();
+                ui.label("Accent color");
+                changed |= ui.color_edit_button_srgb(&mut prefs.accent_color).changed();
+                ui.end_row();
+                ui.label("Autosave every");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut prefs.autosave_minutes).range(1..=120).suffix(" min"))
+                    .changed();
+                ui.end_row();
+                ui.label("Autosave backups");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut prefs.autosave_generations).range(0..=50))
+                    .on_hover_text("Older autosaves to keep per project, offered in Recover Autosave")
+                    .changed();
+                ui.end_row();
+                ui.label("Overlay grid");
+                ui.horizontal(|ui| {
+                    changed |= ui.checkbox(&mut prefs.show_overlay_grid, "Show grid").changed();
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut prefs.overlay_grid_step)
+                                .range(4.0..=500.0)
+                                .suffix(" px"),
+                        )
+                        .on_hover_text("Grid spacing in page units; also the snap step")
+                        .changed();
+                    changed |= ui
+                        .color_edit_button_srgba_unmultiplied(&mut prefs.overlay_grid_color)
+                        .changed();
+                    if ui.small_button("Reset").clicked() {
+                        prefs.overlay_grid_step = DEFAULT_OVERLAY_GRID_STEP;
+                        prefs.overlay_grid_color = DEFAULT_OVERLAY_GRID_COLOR;
+                        changed = true;
+                    }
+                });
+                ui.end_row();
+                ui.label("Controller types");
+                changed |= string_list_editor(ui, "pref_controller_types", &mut prefs.controller_types, &DEFAULT_CONTROLLER_TYPES);
+                ui.end_row();
+                ui.label("License SKUs");
+                changed |= string_list_editor(ui, "pref_controller_licenses", &mut prefs.controller_licenses, &DEFAULT_CONTROLLER_LICENSES);
+                ui.end_row();
+                ui.label("Drawing resolution");
+                ui.horizontal(|ui| {
+                    for scale in PDF_RENDER_SCALES {
+                        if ui
+                            .selectable_label(prefs.pdf_render_scale == scale, format!("{scale:.0}×"))
+                            .clicked()
+                            && prefs.pdf_render_scale != scale
+                        {
+                            prefs.pdf_render_scale = scale;
+                            changed = true;
+                            rerender = true;
+                        }
+                    }
+                });
+                ui.end_row();
+                ui.label("Controller point limit");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut prefs.controller_point_threshold).range(0..=5000).suffix(" pts"))
+                    .on_hover_text("Flag controllers above this many points; 0 only flags ones over twice the average")
+                    .changed();
+                ui.end_row();
+                ui.label("Standard assumptions");
+                changed |= clause_list_editor(ui, "pref_standard_assumptions", &mut prefs.standard_assumptions, &[]);
+                ui.end_row();
+                ui.label("Standard exclusions");
+                changed |= clause_list_editor(ui, "pref_standard_exclusions", &mut prefs.standard_exclusions, &[]);
+                ui.end_row();
+                ui.label("PDF form data");
+                if ui
+                    .checkbox(&mut prefs.render_pdf_form_data, "Render form fields and annotations")
+                    .on_hover_text("Turn off if a drawing shows stale or garbled form overlays")
+                    .changed()
+                {
+                    changed = true;
+                    rerender = true;
+                }
+                ui.end_row();
+            });
+        ui.small("These preferences apply to every project on this workstation.");
+        if rerender {
+            self.request_overlay_render();
+        }
+        if changed {
+            if let Err(err) = self.preferences.save() {
+                self.status = format!("Could not save preferences: {err}");
+            }
+        }
+    }
+
+    /// Finds a child of `parent` by name, creating it with a fresh `next_id` if missing.
+    /// Returns the id and whether it was created.
+    fn find_or_create_child(&mut self, parent: Option<u64>, object_type: ObjectType, name: &str) -> (u64, bool) {
+        if let Some(existing) = self.project.objects.iter().find(|o| {
+            o.parent_id == parent && o.object_type == object_type && o.name.eq_ignore_ascii_case(name)
+        }) {
+            return (existing.id, false);
+        }
+        let id = self.project.next_id;
+        self.project.next_id += 1;
+        self.project.objects.push(BasObject {
+            id,
+            name: name.to_string(),
+            object_type,
+            parent_id: parent,
+            ..Default::default()
+        });
+        (id, true)
+    }
+
+    fn import_points_csv(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+            return;
+        };
+        self.status = match self.import_points_csv_from(&path) {
+            Ok((created, skipped)) => {
+                tracing::info!(path = %path.display(), created, skipped = skipped.len(), "imported points list");
+                let mut status = format!("Imported {created} object(s) from {}", path.display());
+                if !skipped.is_empty() {
+                    let rows: Vec<String> = skipped.iter().map(usize::to_string).collect();
+                    status.push_str(&format!("; skipped row(s) {} with no controller", rows.join(", ")));
+                }
+                status
+            }
+            Err(err) => format!("Points import failed: {err:#}"),
+        };
+    }
+
+    /// Reads controller / equipment / point / kind rows into the selected building
+    /// (or the first one), reusing objects that already exist by name. The first row is
+    /// a header only if one of its cells names a column; otherwise it is imported as
+    /// data. Returns the number of objects created and the 1-based line numbers of rows
+    /// skipped for having no controller.
+    fn import_points_csv_from(&mut self, path: &std::path::Path) -> anyhow::Result<(usize, Vec<usize>)> {
+        use anyhow::Context;
+
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        let mut records = reader.records().peekable();
+        let first: Vec<String> = match records.peek() {
+            Some(Ok(record)) => record.iter().map(str::to_lowercase).collect(),
+            _ => Vec::new(),
+        };
+        let has_header = first
+            .iter()
+            .any(|cell| ["controller", "equipment", "point", "kind"].iter().any(|name| cell.contains(name)));
+        let headers = if has_header {
+            records.next();
+            first
+        } else {
+            Vec::new()
+        };
+        let column = |name: &str, fallback: usize| {
+            headers.iter().position(|h| h.contains(name)).unwrap_or(fallback)
+        };
+        let (c_col, e_col, p_col, k_col) = (
+            column("controller", 0),
+            column("equipment", 1),
+            column("point", 2),
+            column("kind", 3),
+        );
+
+        let building = self
+            .selected_object
+            .and_then(|id| self.project.objects.iter().find(|o| o.id == id))
+            .filter(|o| o.object_type == ObjectType::Building)
+            .or_else(|| self.project.objects.iter().find(|o| o.object_type == ObjectType::Building))
+            .map(|o| o.id);
+        let mut created = 0;
+        let building = match building {
+            Some(id) => id,
+            None => {
+                created += 1;
+                self.find_or_create_child(None, ObjectType::Building, "Imported Building").0
+            }
+        };
+
+        let mut skipped = Vec::new();
+        for record in records {
+            let record = record?;
+            let field = |idx: usize| record.get(idx).unwrap_or("").trim();
+            let (controller, equipment, point) = (field(c_col), field(e_col), field(p_col));
+            if controller.is_empty() {
+                skipped.push(record.position().map_or(0, |pos| pos.line() as usize));
+                continue;
+            }
+            let (controller_id, new) =
+                self.find_or_create_child(Some(building), ObjectType::Controller, controller);
+            created += usize::from(new);
+            if equipment.is_empty() {
+                continue;
+            }
+            let (equipment_id, new) =
+                self.find_or_create_child(Some(controller_id), ObjectType::Equipment, equipment);
+            created += usize::from(new);
+            if point.is_empty() {
+                continue;
+            }
+            let (point_id, new) = self.find_or_create_child(Some(equipment_id), ObjectType::Point, point);
+            created += usize::from(new);
+            if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == point_id) {
+                obj.point_kind = parse_point_kind(field(k_col));
+            }
+        }
+        if created > 0 {
+            self.mark_dirty();
+        }
+        Ok((created, skipped))
+    }
+
+    /// Library toolbar at the top of `templates_view`.
+    fn template_library_actions(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("📥 Import Templates").clicked() {
+                self.import_templates();
+            }
+            let export_label = if self.template_export_selection.is_empty() {
+                "📤 Export All Templates".to_string()
+            } else {
+                format!("📤 Export {} Selected", self.template_export_selection.len())
+            };
+            if ui.button(export_label).clicked() {
+                self.export_templates();
+            }
+        });
+    }
+
+    fn import_templates(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("Templates", &["json"]).pick_file() else {
+            return;
+        };
+        let incoming: Vec<EquipmentTemplate> = match std::fs::read(&path)
+            .map_err(AppIoError::from)
+            .and_then(|bytes| UserTemplateStore::from_slice(&bytes).map_err(AppIoError::from))
+        {
+            Ok(store) => store.equipment,
+            Err(err) => {
+                self.status = format!("Template import failed: {err}");
+                return;
+            }
+        };
+        let collides = incoming
+            .iter()
+            .any(|t| self.user_templates.iter().any(|u| u.name == t.name));
+        if collides {
+            self.pending_template_import = Some(incoming);
+        } else {
+            self.merge_templates(incoming, false);
+        }
+    }
+
+    /// Merges by name the way `ensure_template_seeded` dedupes, then persists.
+    fn merge_templates(&mut self, incoming: Vec<EquipmentTemplate>, overwrite: bool) {
+        let (mut added, mut replaced, mut skipped) = (0, 0, 0);
+        for template in incoming {
+            match self.user_templates.iter_mut().find(|t| t.name == template.name) {
+                Some(existing) if overwrite => {
+                    *existing = template;
+                    replaced += 1;
+                }
+                Some(_) => skipped += 1,
+                None => {
+                    self.user_templates.push(template);
+                    added += 1;
+                }
+            }
+        }
+        self.save_user_templates();
+        self.status = format!("Templates imported: {added} added, {replaced} replaced, {skipped} skipped");
+    }
+
+    fn template_import_dialog(&mut self, ctx: &egui::Context) {
+        let Some(incoming) = &self.pending_template_import else {
+            return;
+        };
+        let collisions: Vec<String> = incoming
+            .iter()
+            .filter(|t| self.user_templates.iter().any(|u| u.name == t.name))
+            .map(|t| t.name.clone())
+            .collect();
+        let mut choice = None;
+        egui::Window::new("Template Name Conflicts")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} of {} imported templates already exist:",
+                    collisions.len(),
+                    incoming.len()
+                ));
+                egui::ScrollArea::vertical().max_height(180.0).show(ui, |ui| {
+                    for name in &collisions {
+                        ui.label(format!("• {name}"));
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Skip existing").clicked() {
+                        choice = Some(Some(false));
+                    }
+                    if ui.button("Overwrite existing").clicked() {
+                        choice = Some(Some(true));
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(None);
+                    }
+                });
+            });
+        if let Some(choice) = choice {
+            let incoming = self.pending_template_import.take().unwrap_or_default();
+            match choice {
+                Some(overwrite) => self.merge_templates(incoming, overwrite),
+                None => self.status = "Template import cancelled".to_string(),
+            }
+        }
+    }
+
+    /// Per-card checkbox marking a template for export.
+    fn template_export_checkbox(&mut self, ui: &mut Ui, name: &str) {
+        let mut checked = self.template_export_selection.contains(name);
+        if ui.checkbox(&mut checked, "").on_hover_text("Include in export").changed() {
+            if checked {
+                self.template_export_selection.insert(name.to_string());
+            } else {
+                self.template_export_selection.remove(name);
+            }
+        }
+    }
+
+    /// Writes the selected templates (or all of them) in the same format as the user
+    /// template store, so the file round-trips through Import Templates.
+    fn export_templates(&mut self) {
+        let templates: Vec<EquipmentTemplate> = self
+            .user_templates
+            .iter()
+            .filter(|t| {
+                self.template_export_selection.is_empty() || self.template_export_selection.contains(&t.name)
+            })
+            .cloned()
+            .collect();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Templates", &["json"])
+            .set_file_name("automate_templates.json")
+            .save_file()
+        else {
+            return;
+        };
+        let count = templates.len();
+        let export = UserTemplateStore {
+            equipment: templates,
+            controllers: Vec::new(),
+        };
+        self.status = match export.write(&path) {
+            Ok(()) => format!("Exported {count} template(s) to {}", path.display()),
+            Err(err) => format!("Template export failed: {err}"),
+        };
+    }
+
+    /// Writes equipment and controller templates back to the user template store.
+    fn save_user_templates(&mut self) {
+        // Until the stored library is in memory, writing would replace it with a partial
+        // list; `poll_startup_preload` saves once it has merged.
+        if self.startup_preload_rx.is_some() {
+            return;
+        }
+        let store = UserTemplateStore {
+            equipment: self.user_templates.clone(),
+            controllers: self.controller_templates.clone(),
+        };
+        if let Err(err) = store.write(&templates_store_path()) {
+            self.status = format!("Could not save templates: {err}");
+        }
+    }
+
+    /// Controller template cards for the Templates view.
+    fn controller_templates_section(&mut self, ui: &mut Ui) {
+        let equipment_names: Vec<String> = self.user_templates.iter().map(|t| t.name.clone()).collect();
+        let mut changed = false;
+        let mut remove = None;
+        egui::CollapsingHeader::new(RichText::new("🖧 Controller Templates").strong())
+            .default_open(true)
+            .show(ui, |ui| {
+                for (idx, template) in self.controller_templates.iter_mut().enumerate() {
+                    ui.push_id(("controller_template", idx), |ui| {
+                        Self::card_frame().show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                changed |= ui.text_edit_singleline(&mut template.name).changed();
+                                if ui.small_button("🗑").clicked() {
+                                    remove = Some(idx);
+                                }
+                            });
+                            egui::Grid::new("controller_template_grid").num_columns(2).show(ui, |ui| {
+                                ui.label("Type");
+                                changed |= ui.text_edit_singleline(&mut template.controller_type).changed();
+                                ui.end_row();
+                                ui.label("License");
+                                changed |= ui.text_edit_singleline(&mut template.controller_license).changed();
+                                ui.end_row();
+                            });
+                            ui.collapsing(format!("Equipment ({})", template.equipment.len()), |ui| {
+                                for name in &equipment_names {
+                                    let count = template.equipment.iter().filter(|e| *e == name).count();
+                                    ui.horizontal(|ui| {
+                                        if ui.add_enabled(count > 0, egui::Button::new("➖").small()).clicked() {
+                                            if let Some(pos) = template.equipment.iter().position(|e| e == name) {
+                                                template.equipment.remove(pos);
+                                                changed = true;
+                                            }
+                                        }
+                                        ui.monospace(count.to_string());
+                                        if ui.small_button("➕").clicked() {
+                                            template.equipment.push(name.clone());
+                                            changed = true;
+                                        }
+                                        ui.label(name);
+                                    });
+                                }
+                            });
+                        });
+                    });
+                }
+                if ui.button("➕ New Controller Template").clicked() {
+                    self.controller_templates.push(ControllerTemplate {
+                        name: format!("Controller Template {}", self.controller_templates.len() + 1),
+                        ..Default::default()
+                    });
+                    changed = true;
+                }
+            });
+        if let Some(idx) = remove {
+            self.controller_templates.remove(idx);
+            changed = true;
+        }
+        if changed {
+            self.save_user_templates();
+        }
+    }
+
+    /// Stamps type and license onto the controller and creates the template's equipment
+    /// as children, each synced from its equipment template.
+    fn generate_controller_from_template(&mut self, controller_id: u64, template_name: &str) {
+        let Some(template) = self
+            .controller_templates
+            .iter()
+            .find(|t| t.name == template_name)
+            .cloned()
+        else {
+            return;
+        };
+        if !self.project.objects.iter().any(|o| o.id == controller_id) {
+            return;
+        }
+        self.push_project_history();
+        let Some(controller) = self.project.objects.iter_mut().find(|o| o.id == controller_id) else {
+            return;
+        };
+        controller.controller_type = template.controller_type.clone();
+        controller.controller_license = template.controller_license.clone();
+        for equipment_template in &template.equipment {
+            self.create_templated_equipment(controller_id, equipment_template, equipment_template);
+        }
+        self.mark_dirty();
+        self.status = format!(
+            "Generated {} equipment from {template_name}",
+            template.equipment.len()
+        );
+    }
+
+    /// "Generate from Template" menu shown in a selected controller's properties.
+    fn controller_template_picker(&mut self, ui: &mut Ui, controller_id: u64) {
+        if self.controller_templates.is_empty() {
+            return;
+        }
+        let mut chosen = None;
+        ui.menu_button("🧩 Generate from Template", |ui| {
+            for template in &self.controller_templates {
+                let hover = format!(
+                    "{} / {} with {} equipment",
+                    template.controller_type,
+                    template.controller_license,
+                    template.equipment.len()
+                );
+                if ui.button(&template.name).on_hover_text(hover).clicked() {
+                    chosen = Some(template.name.clone());
+                    ui.close_menu();
+                }
+            }
+        });
+        if let Some(name) = chosen {
+            self.generate_controller_from_template(controller_id, &name);
+        }
+    }
+
+    /// Template indices keyed by category, with "Uncategorized" sorted last.
+    fn templates_by_category(&self, indices: &[usize]) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for &idx in indices {
+            if let Some(template) = self.user_templates.get(idx) {
+                groups
+                    .entry(template_category(template).to_string())
+                    .or_default()
+                    .push(idx);
+            }
+        }
+        let uncategorized = groups.remove(UNCATEGORIZED_TEMPLATES);
+        let mut ordered: Vec<(String, Vec<usize>)> = groups.into_iter().collect();
+        if let Some(indices) = uncategorized {
+            ordered.push((UNCATEGORIZED_TEMPLATES.to_string(), indices));
+        }
+        ordered
+    }
+
+    /// Renders template cards under collapsible category headers in `templates_view`.
+    fn template_category_sections(
+        &mut self,
+        ui: &mut Ui,
+        indices: &[usize],
+        mut card: impl FnMut(&mut Self, &mut Ui, usize),
+    ) {
+        for (category, members) in self.templates_by_category(indices) {
+            egui::CollapsingHeader::new(RichText::new(format!("{category} ({})", members.len())).strong())
+                .id_source(("template_category", &category))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for idx in members {
+                        ui.push_id(("template_card", idx), |ui| card(self, ui, idx));
+                    }
+                });
+        }
+    }
+
+    /// Category field for a template card; returns `true` when edited.
+    fn template_category_editor(ui: &mut Ui, template: &mut EquipmentTemplate) -> bool {
+        ui.horizontal(|ui| {
+            ui.label("Category");
+            ui.add(
+                egui::TextEdit::singleline(&mut template.category)
+                    .hint_text(UNCATEGORIZED_TEMPLATES)
+                    .desired_width(160.0),
+            )
+            .changed()
+        })
+        .inner
+    }
+
+    /// Point Template combo for equipment in `right_properties`, grouped by category.
+    fn point_template_combo(&mut self, ui: &mut Ui, id: u64) {
+        let Some(current) = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == id)
+            .map(|o| o.template_name.clone())
+        else {
+            return;
+        };
+        let indices: Vec<usize> = self
+            .user_templates
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| self.show_archived_templates || !t.archived || t.name == current)
+            .map(|(idx, _)| idx)
+            .collect();
+        let mut chosen = None;
+        let selected_text = if current.is_empty() { "None".to_string() } else { current.clone() };
+        egui::ComboBox::from_id_source(("point_template", id))
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(current.is_empty(), "None").clicked() {
+                    chosen = Some(String::new());
+                }
+                for (category, members) in self.templates_by_category(&indices) {
+                    ui.separator();
+                    ui.label(RichText::new(category).small().weak());
+                    for idx in members {
+                        let template = &self.user_templates[idx];
+                        let name = &template.name;
+                        let label = if template.archived {
+                            format!("  {name} (archived)")
+                        } else {
+                            format!("  {name}")
+                        };
+                        if ui.selectable_label(*name == current, label).clicked() {
+                            chosen = Some(name.clone());
+                        }
+                    }
+                }
+            });
+        let Some(name) = chosen.filter(|name| *name != current) else {
+            return;
+        };
+        if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) {
+            obj.template_name = name;
+        }
+        self.sync_equipment_from_template(id);
+        self.mark_dirty();
+    }
+
+    /// Older libraries marked archives by putting "archive" in the name; carry that over
+    /// to the `archived` flag once so later renames don't un-archive them. Runs when the
+    /// stored library first loads; `archived_templates_migrated` keeps it from running
+    /// again and re-archiving a template the user restored.
+    fn migrate_archived_templates(&mut self) {
+        if self.preferences.archived_templates_migrated {
+            return;
+        }
+        self.preferences.archived_templates_migrated = true;
+        if let Err(err) = self.preferences.save() {
+            tracing::warn!(%err, "could not record the template archive migration");
+        }
+        let mut migrated = false;
+        for template in &mut self.user_templates {
+            if !template.archived && legacy_archive_name(&template.name) {
+                template.archived = true;
+                migrated = true;
+            }
+        }
+        if migrated {
+            self.save_user_templates();
+        }
+    }
+
+    /// Archive/restore toggle for a template card; returns `true` when flipped.
+    fn template_archive_toggle(ui: &mut Ui, template: &mut EquipmentTemplate) -> bool {
+        let (label, hover) = if template.archived {
+            ("♻ Restore", "Show this template in the Point Template list again")
+        } else {
+            ("🗄 Archive", "Hide this template from the Point Template list")
+        };
+        if ui.small_button(label).on_hover_text(hover).clicked() {
+            template.archived = !template.archived;
+            return true;
+        }
+        false
+    }
+
+    /// Indices of `user_templates` that pass the Templates tool search box and the
+    /// archived checkbox. The list itself is never reordered or filtered in place.
+    fn visible_template_indices(&self) -> Vec<usize> {
+        let query = self.template_search.trim().to_lowercase();
+        self.user_templates
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| self.show_archived_templates || !t.archived)
+            .filter(|(_, t)| {
+                query.is_empty()
+                    || t.name.to_lowercase().contains(&query)
+                    || t.equipment_type.to_lowercase().contains(&query)
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Search box and archived toggle shown at the top of `templates_view`.
+    fn template_search_bar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.template_search)
+                    .hint_text("🔍 Filter by name or equipment type")
+                    .desired_width(240.0),
+            );
+            if !self.template_search.is_empty() && ui.small_button("✖").clicked() {
+                self.template_search.clear();
+            }
+            ui.checkbox(&mut self.show_archived_templates, "Show archived templates");
+            let shown = self.visible_template_indices().len();
+            ui.small(format!("{shown} of {}", self.user_templates.len()));
+        });
+    }
+
+    /// First of "`base` Copy", "`base` Copy 2", ... not already used in `user_templates`,
+    /// so `ensure_template_seeded`'s name dedupe never drops the duplicate.
+    fn unique_template_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.user_templates.iter().any(|t| t.name == name);
+        let mut candidate = format!("{base} Copy");
+        let mut n = 2;
+        while taken(&candidate) {
+            candidate = format!("{base} Copy {n}");
+            n += 1;
+        }
+        candidate
+    }
+
+    fn duplicate_template(&mut self, idx: usize) {
+        let Some(mut copy) = self.user_templates.get(idx).cloned() else {
+            return;
+        };
+        copy.name = self.unique_template_name(&copy.name);
+        self.status = format!("Duplicated template as {}", copy.name);
+        self.user_templates.insert(idx + 1, copy);
+        self.save_user_templates();
+    }
+
+    fn template_duplicate_button(&mut self, ui: &mut Ui, idx: usize) {
+        if ui.small_button("⎘ Duplicate").on_hover_text("Copy this template").clicked() {
+            self.duplicate_template(idx);
+        }
+    }
+
+    /// Child points of `equipment_id` whose kind differs from the same-named point in
+    /// its template, as (point id, template kind). `sync_equipment_from_template` leaves
+    /// existing names alone, so these drift when a template point's kind is edited.
+    fn point_kind_mismatches(&self, equipment_id: u64) -> Vec<(u64, PointKind)> {
+        let Some(template) = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == equipment_id)
+            .and_then(|e| self.user_templates.iter().find(|t| t.name == e.template_name))
+        else {
+            return Vec::new();
+        };
+        self.project
+            .objects
+            .iter()
+            .filter(|o| o.parent_id == Some(equipment_id) && o.object_type == ObjectType::Point)
+            .filter_map(|o| {
+                let point = template.points.iter().find(|p| p.name == o.name)?;
+                (point.kind != o.point_kind).then_some((o.id, point.kind))
+            })
+            .collect()
+    }
+
+    /// Sets each mismatched point's kind to its template's. Only run from an explicit
+    /// button so per-point overrides aren't clobbered behind the user's back.
+    fn resync_point_kinds(&mut self, equipment_id: u64) -> usize {
+        let mismatches = self.point_kind_mismatches(equipment_id);
+        for (id, kind) in &mismatches {
+            if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == *id) {
+                obj.point_kind = *kind;
+            }
+        }
+        mismatches.len()
+    }
+
+    /// "Resync kinds" for equipment in `right_properties`; disabled when every point
+    /// already matches its template.
+    fn resync_point_kinds_button(&mut self, ui: &mut Ui, equipment_id: u64) {
+        let mismatches = self.point_kind_mismatches(equipment_id);
+        let hover = if mismatches.is_empty() {
+            "Point kinds match the template".to_string()
+        } else {
+            mismatches
+                .iter()
+                .filter_map(|(id, kind)| {
+                    let obj = self.project.objects.iter().find(|o| o.id == *id)?;
+                    Some(format!("{}: {:?} → {kind:?}", obj.name, obj.point_kind))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let label = format!("Resync kinds ({})", mismatches.len());
+        if ui
+            .add_enabled(!mismatches.is_empty(), egui::Button::new(label))
+            .on_hover_text(hover)
+            .on_disabled_hover_text("Point kinds match the template")
+            .clicked()
+        {
+            self.push_project_history();
+            let updated = self.resync_point_kinds(equipment_id);
+            self.mark_dirty();
+            self.status = format!("Updated the kind of {updated} point(s) from the template");
+        }
+    }
+
+    /// Extra Help-menu entries rendered by `toolbar_dropdowns`.
+    fn help_menu_extras(&mut self, ui: &mut Ui) {
+        if ui.button("🚪 Log out").clicked() {
+            ui.close_menu();
+            self.request_discard(&ui.ctx().clone(), DiscardAction::LogOut);
+        }
+    }
+
+    /// Called by the login card once credentials are accepted; remembers the operator ID
+    /// for the next launch.
+    fn remember_operator_id(&mut self) {
+        if self.preferences.last_operator_id == self.login_username {
+            return;
+        }
+        self.preferences.last_operator_id = self.login_username.clone();
+        if let Err(err) = self.preferences.save() {
+            tracing::warn!(%err, "could not persist last operator ID");
+        }
+    }
+
+    /// Confirm-passphrase field shown by `login_screen` on first run, when no
+    /// credentials file exists yet.
+    fn first_run_password_fields(&mut self, ui: &mut Ui) {
+        if self.credentials.is_some() {
+            return;
+        }
+        ui.label(RichText::new("First run: choose a passphrase for this workstation.").weak());
+        ui.add(
+            egui::TextEdit::singleline(&mut self.login_password_confirm)
+                .password(true)
+                .hint_text("Confirm passphrase"),
+        );
+    }
+
+    /// Backs the Authenticate button. Checks the passphrase against the stored hash,
+    /// or on first run stores a new hash for the entered operator ID.
+    fn authenticate(&mut self) {
+        let operator_id = self.login_username.trim().to_string();
+        if operator_id.is_empty() || self.login_password.is_empty() {
+            self.login_error = Some("Enter an operator ID and passphrase.".to_string());
+            return;
+        }
+        match &self.credentials {
+            Some(credentials) => {
+                if !credentials.verify(&operator_id, &self.login_password) {
+                    self.login_error = Some("Operator ID or passphrase is incorrect.".to_string());
+                    self.login_password.clear();
+                    return;
+                }
+            }
+            None => {
+                if self.login_password != self.login_password_confirm {
+                    self.login_error = Some("Passphrases do not match.".to_string());
+                    return;
+                }
+                let created = LocalCredentials::create(&operator_id, &self.login_password);
+                match created.and_then(|credentials| credentials.save().map(|()| credentials)) {
+                    Ok(credentials) => self.credentials = Some(credentials),
+                    Err(err) => {
+                        self.login_error = Some(format!("Could not store credentials: {err}"));
+                        return;
+                    }
+                }
+            }
+        }
+        self.login_error = None;
+        self.login_password.clear();
+        self.login_password_confirm.clear();
+        self.remember_operator_id();
+        self.app_screen = AppScreen::Studio;
+    }
+
+    /// Page size in overlay units: the rendered texture, or the default blank canvas.
+    fn overlay_page_size(&self) -> egui::Vec2 {
+        self.overlay_texture
+            .as_ref()
+            .map(|texture| texture.size_vec2() / self.overlay_texture_scale.max(f32::EPSILON))
+            .filter(|size| size.x > 0.0 && size.y > 0.0)
+            .unwrap_or(OVERLAY_DEFAULT_CANVAS)
+    }
+
+    /// Zooms so the whole page (or just its width) fits `viewport`, centered horizontally.
+    fn fit_overlay(&mut self, viewport: egui::Vec2, width_only: bool) {
+        let page = self.overlay_page_size();
+        let zoom = if width_only {
+            viewport.x / page.x
+        } else {
+            (viewport.x / page.x).min(viewport.y / page.y)
+        };
+        self.overlay_zoom = zoom.clamp(OVERLAY_ZOOM_MIN, OVERLAY_ZOOM_MAX);
+        let scaled = page * self.overlay_zoom;
+        self.overlay_pan = egui::vec2(
+            ((viewport.x - scaled.x) * 0.5).max(0.0),
+            if width_only { 0.0 } else { ((viewport.y - scaled.y) * 0.5).max(0.0) },
+        );
+    }
+
+    /// "Fit Page" / "Fit Width" buttons beside the overlay zoom controls.
+    fn overlay_fit_buttons(&mut self, ui: &mut Ui, viewport: egui::Vec2) {
+        if ui.button("⛶ Fit Page").on_hover_text("Show the whole sheet").clicked() {
+            self.fit_overlay(viewport, false);
+        }
+        if ui.button("↔ Fit Width").on_hover_text("Fill the view with the sheet width").clicked() {
+            self.fit_overlay(viewport, true);
+        }
+    }
+
+    /// Multiplies `overlay_zoom` by `factor` while keeping the page point under `anchor`
+    /// (canvas-local) fixed on screen.
+    fn zoom_overlay_about(&mut self, anchor: egui::Vec2, factor: f32) {
+        let old_zoom = self.overlay_zoom.max(f32::EPSILON);
+        let new_zoom = (old_zoom * factor).clamp(OVERLAY_ZOOM_MIN, OVERLAY_ZOOM_MAX);
+        let applied = new_zoom / old_zoom;
+        self.overlay_pan = anchor - (anchor - self.overlay_pan) * applied;
+        self.overlay_zoom = new_zoom;
+    }
+
+    /// Ctrl+scroll over the canvas zooms toward the pointer.
+    fn overlay_scroll_zoom(&mut self, ui: &Ui, response: &egui::Response, canvas_min: egui::Pos2) {
+        let Some(pointer) = response.hover_pos() else {
+            return;
+        };
+        let factor = ui.input(|i| if i.modifiers.command { i.zoom_delta() } else { 1.0 });
+        if (factor - 1.0).abs() > f32::EPSILON {
+            self.zoom_overlay_about(pointer - canvas_min, factor);
+        }
+    }
+
+    /// ➖/➕ zoom buttons; these anchor on the viewport center.
+    fn overlay_zoom_buttons(&mut self, ui: &mut Ui, viewport: egui::Vec2) {
+        if ui.button("➖").clicked() {
+            self.zoom_overlay_about(viewport * 0.5, 1.0 / 1.25);
+        }
+        ui.monospace(format!("{:>4.0}%", self.overlay_zoom * 100.0));
+        if ui.button("➕").clicked() {
+            self.zoom_overlay_about(viewport * 0.5, 1.25);
+        }
+    }
+
+    /// "Snap to grid" toggle for the overlay toolbar.
+    fn overlay_snap_toggle(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.overlay_snap_to_grid, "Snap to grid")
+            .on_hover_text(format!(
+                "Round placements to the {:.0}px grid",
+                self.preferences.overlay_grid_step
+            ));
+    }
+
+    fn overlay_snap_step(&self) -> Option<f32> {
+        self.overlay_snap_to_grid
+            .then_some(self.preferences.overlay_grid_step)
+            .filter(|step| *step > 0.0)
+    }
+
+    /// Grid lines for `drawings_overlay_view`, in page space so they scale and pan with
+    /// the drawing.
+    fn paint_overlay_grid(&self, painter: &egui::Painter, canvas: egui::Rect) {
+        if !self.preferences.show_overlay_grid {
+            return;
+        }
+        let step = self.preferences.overlay_grid_step * self.overlay_zoom;
+        // Skip grids too dense to read rather than painting thousands of lines.
+        if step < 4.0 {
+            return;
+        }
+        let [r, g, b, a] = self.preferences.overlay_grid_color;
+        let stroke = egui::Stroke::new(1.0, Color32::from_rgba_unmultiplied(r, g, b, a));
+        let origin = canvas.min + self.overlay_pan;
+        let mut x = origin.x + ((canvas.min.x - origin.x) / step).ceil() * step;
+        while x <= canvas.max.x {
+            painter.line_segment([egui::pos2(x, canvas.min.y), egui::pos2(x, canvas.max.y)], stroke);
+            x += step;
+        }
+        let mut y = origin.y + ((canvas.min.y - origin.y) / step).ceil() * step;
+        while y <= canvas.max.y {
+            painter.line_segment([egui::pos2(canvas.min.x, y), egui::pos2(canvas.max.x, y)], stroke);
+            y += step;
+        }
+    }
+
+    /// Converts a canvas click to page space, snapped when the toggle is on. Used for
+    /// token placement and line endpoints.
+    fn overlay_placement_point(&self, canvas_min: egui::Pos2, pos: egui::Pos2) -> [f32; 2] {
+        snap_overlay_point(
+            self.overlay_snap_step(),
+            overlay_to_page(canvas_min, self.overlay_pan, self.overlay_zoom, pos),
+        )
+    }
+
+    fn project_snapshot(&self) -> ProjectSnapshot {
+        ProjectSnapshot {
+            objects: self.project.objects.clone(),
+            next_id: self.project.next_id,
+            selected_object: self.selected_object,
+        }
+    }
+
+    fn restore_project_snapshot(&mut self, snapshot: ProjectSnapshot) {
+        self.project.objects = snapshot.objects;
+        self.project.next_id = snapshot.next_id;
+        self.selected_object = snapshot.selected_object;
+        self.selected_objects.clear();
+        self.mark_dirty();
+    }
+
+    /// Call before `add_object`, `duplicate_object`, `remove_object_subtree`,
+    /// `reparent_object` and other structural tree edits.
+    fn push_project_history(&mut self) {
+        self.project_undo.push(self.project_snapshot());
+        if self.project_undo.len() > PROJECT_HISTORY_LIMIT {
+            self.project_undo.remove(0);
+        }
+        self.project_redo.clear();
+    }
+
+    fn project_undo(&mut self) {
+        let Some(snapshot) = self.project_undo.pop() else {
+            return;
+        };
+        self.project_redo.push(self.project_snapshot());
+        self.restore_project_snapshot(snapshot);
+        self.status = "Undid tree edit".to_string();
+    }
+
+    fn project_redo(&mut self) {
+        let Some(snapshot) = self.project_redo.pop() else {
+            return;
+        };
+        self.project_undo.push(self.project_snapshot());
+        self.restore_project_snapshot(snapshot);
+        self.status = "Redid tree edit".to_string();
+    }
+
+    /// Cmd+Z / Cmd+Shift+Z outside the overlay, where `handle_shortcuts` keeps routing
+    /// them to `overlay_undo`/`overlay_redo`.
+    fn handle_project_history_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.app_screen != AppScreen::Studio || self.current_view == ToolView::DrawingsOverlay {
+            return;
+        }
+        let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z);
+        let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+        // Redo first: the plain Cmd+Z shortcut would also match with Shift held.
+        if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+            self.project_redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+            self.project_undo();
+        }
+    }
+
+    /// Plain-text estimate for pasting into chat.
+    fn estimate_summary_text(&self) -> String {
+        let [eng, gfx, cx, custom, overhead, total] = self.estimate_hours().totals;
+        let count = |kind: ObjectType| self.project.objects.iter().filter(|o| o.object_type == kind).count();
+        format!(
+            "{} (Rev {})\n\
+             Objects: {} buildings, {} controllers, {} equipment, {} points\n\
+             Engineering {eng:.1} h | Graphics {gfx:.1} h | Commissioning {cx:.1} h\n\
+             Other custom {custom:.1} h | Overhead {overhead:.1} h\n\
+             Total {total:.1} h",
+            self.project.name,
+            self.project.proposal.revision,
+            count(ObjectType::Building),
+            count(ObjectType::Controller),
+            count(ObjectType::Equipment),
+            count(ObjectType::Point),
+        )
+    }
+
+    /// "Copy summary" button for `hours_estimator_view`.
+    fn copy_estimate_summary_button(&mut self, ui: &mut Ui) {
+        if ui.button("📋 Copy summary").on_hover_text("Copy the hours summary as text").clicked() {
+            ui.ctx().copy_text(self.estimate_summary_text());
+            self.status = "Estimate summary copied to clipboard".to_string();
+        }
+    }
+
+    fn save_estimate_snapshot(&mut self) {
+        let label = match self.snapshot_label.trim() {
+            "" => format!("Snapshot {}", self.project.snapshots.len() + 1),
+            label => label.to_string(),
+        };
+        self.project.snapshots.push(EstimateSnapshot {
+            label: label.clone(),
+            taken_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            estimator: self.project.estimator.clone(),
+            totals: self.estimate_hours().totals,
+        });
+        self.snapshot_label.clear();
+        self.mark_dirty();
+        self.status = format!("Saved estimate snapshot {label}");
+    }
+
+    /// Snapshot capture and comparison table for `hours_estimator_view`. Each cell
+    /// shows the snapshot's hours and the live estimate's delta against it.
+    fn estimate_snapshots_card(&mut self, ui: &mut Ui) {
+        Self::card_frame().show(ui, |ui| {
+            ui.heading("Snapshots");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.snapshot_label)
+                        .hint_text("Label")
+                        .desired_width(180.0),
+                );
+                if ui.button("📸 Save Snapshot").clicked() {
+                    self.save_estimate_snapshot();
+                }
+            });
+            if self.project.snapshots.is_empty() {
+                ui.small("No snapshots yet.");
+                return;
+            }
+            let live = self.estimate_hours().totals;
+            let mut remove = None;
+            egui::Grid::new("estimate_snapshots")
+                .striped(true)
+                .num_columns(ESTIMATE_COLUMNS.len() + 2)
+                .show(ui, |ui| {
+                    ui.strong("Snapshot");
+                    for column in ESTIMATE_COLUMNS {
+                        ui.strong(column);
+                    }
+                    ui.label("");
+                    ui.end_row();
+                    ui.label(RichText::new("Live").italics());
+                    for value in live {
+                        ui.monospace(format!("{value:.1}"));
+                    }
+                    ui.label("");
+                    ui.end_row();
+                    for (idx, snapshot) in self.project.snapshots.iter().enumerate() {
+                        ui.label(&snapshot.label).on_hover_text(&snapshot.taken_at);
+                        for (value, current) in snapshot.totals.iter().zip(live) {
+                            let delta = current - value;
+                            let color = if delta.abs() < 0.05 {
+                                ui.visuals().weak_text_color()
+                            } else if delta > 0.0 {
+                                Color32::from_rgb(230, 120, 90)
+                            } else {
+                                Color32::from_rgb(110, 200, 120)
+                            };
+                            ui.horizontal(|ui| {
+                                ui.monospace(format!("{value:.1}"));
+                                ui.label(RichText::new(format!("{delta:+.1}")).small().color(color));
+                            });
+                        }
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(idx) = remove {
+                self.project.snapshots.remove(idx);
+                self.mark_dirty();
+            }
+        });
+    }
+
+    /// Engineering, graphics and commissioning hours of one equipment: its
+    /// `estimate_hours` row, or zeros when it has none.
+    fn equipment_hours(&self, equipment_id: u64) -> [f32; 3] {
+        self.estimate_hours()
+            .rows
+            .iter()
+            .find(|row| row.object_id == equipment_id)
+            .map_or([0.0; 3], |row| row.hours)
+    }
+
+    /// Commissioning multiplier input under the hours-override controls in
+    /// `right_properties`; hidden while the override is off.
+    fn commissioning_multiplier_field(&mut self, ui: &mut Ui, equipment_id: u64) {
+        let Some(equipment) = self.project.objects.iter_mut().find(|o| o.id == equipment_id) else {
+            return;
+        };
+        if !equipment.hours_override {
+            return;
+        }
+        let changed = ui
+            .horizontal(|ui| {
+                ui.label("Commissioning ×");
+                let changed = ui
+                    .add(
+                        egui::DragValue::new(&mut equipment.commissioning_multiplier)
+                            .speed(0.05)
+                            .range(0.0..=10.0)
+                            .fixed_decimals(2),
+                    )
+                    .on_hover_text("Scales only this equipment's commissioning hours; 1.0 leaves them as estimated")
+                    .changed();
+                if equipment.commissioning_multiplier != 1.0 && ui.small_button("Reset").clicked() {
+                    equipment.commissioning_multiplier = 1.0;
+                    return true;
+                }
+                changed
+            })
+            .inner;
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    /// Nearest ancestor of `id` (inclusive) with the given type, walking `parent_id`.
+    fn ancestor_of_type(&self, id: u64, kind: ObjectType) -> Option<&BasObject> {
+        let mut current = self.project.objects.iter().find(|o| o.id == id);
+        while let Some(obj) = current {
+            if obj.object_type == kind {
+                return Some(obj);
+            }
+            current = obj
+                .parent_id
+                .and_then(|parent| self.project.objects.iter().find(|o| o.id == parent));
+        }
+        None
+    }
+
+    /// Per-building and per-controller rollups of the `estimate_hours` rows, in tree
+    /// order. Custom lines and overhead stay project-wide.
+    fn estimate_breakdown(&self) -> (Vec<HoursRollup>, Vec<HoursRollup>) {
+        let mut buildings: Vec<HoursRollup> = Vec::new();
+        let mut controllers: Vec<HoursRollup> = Vec::new();
+        fn add(rows: &mut Vec<HoursRollup>, obj: &BasObject, hours: [f32; 3]) {
+            let idx = match rows.iter().position(|r| r.id == obj.id) {
+                Some(idx) => idx,
+                None => {
+                    rows.push(HoursRollup {
+                        id: obj.id,
+                        name: obj.name.clone(),
+                        ..Default::default()
+                    });
+                    rows.len() - 1
+                }
+            };
+            rows[idx].engineering += hours[0];
+            rows[idx].graphics += hours[1];
+            rows[idx].commissioning += hours[2];
+        }
+        let object = |id: Option<u64>| id.and_then(|id| self.project.objects.iter().find(|o| o.id == id));
+        for row in self.estimate_hours().rows {
+            if let Some(building) = object(row.building_id) {
+                add(&mut buildings, building, row.hours);
+            }
+            if let Some(controller) = object(row.controller_id) {
+                add(&mut controllers, controller, row.hours);
+            }
+        }
+        (buildings, controllers)
+    }
+
+    /// Collapsible building/controller breakdown for `hours_estimator_view`.
+    fn hours_breakdown_card(&self, ui: &mut Ui) {
+        let (buildings, controllers) = self.estimate_breakdown();
+        for (title, rows) in [("By Building", buildings), ("By Controller", controllers)] {
+            egui::CollapsingHeader::new(RichText::new(title).strong())
+                .id_source(("hours_breakdown", title))
+                .show(ui, |ui| {
+                    if rows.is_empty() {
+                        ui.small("No equipment yet.");
+                        return;
+                    }
+                    egui::Grid::new(("hours_breakdown_grid", title))
+                        .striped(true)
+                        .num_columns(5)
+                        .show(ui, |ui| {
+                            for header in ["Name", "Eng", "Gfx", "Cx", "Total"] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+                            for row in &rows {
+                                ui.label(&row.name);
+                                for value in [row.engineering, row.graphics, row.commissioning, row.total()] {
+                                    ui.monospace(format!("{value:.1}"));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+    }
+
+    /// Custom line hours per category: engineering, graphics, commissioning, other.
+    fn custom_hours_by_category(&self) -> [f32; 4] {
+        let mut buckets = [0.0; 4];
+        for line in &self.project.custom_hour_lines {
+            let idx = match line.category {
+                CustomHourCategory::Engineering => 0,
+                CustomHourCategory::Graphics => 1,
+                CustomHourCategory::Commissioning => 2,
+                CustomHourCategory::Other => 3,
+            };
+            buckets[idx] += line.hours;
+        }
+        buckets
+    }
+
+    /// Summary-grid rows showing how much each category got from custom lines.
+    fn custom_contribution_rows(&self, ui: &mut Ui) {
+        let labels = ["Engineering", "Graphics", "Commissioning", "Other"];
+        for (label, hours) in labels.iter().zip(self.custom_hours_by_category()) {
+            if hours == 0.0 {
+                continue;
+            }
+            ui.label(RichText::new(format!("  incl. custom {label}")).weak());
+            ui.monospace(format!("{hours:.1} h"));
+            ui.end_row();
+        }
+    }
+
+    /// Expense line editor for `hours_estimator_view`, totalled in dollars.
+    fn expenses_card(&mut self, ui: &mut Ui) {
+        Self::card_frame().show(ui, |ui| {
+            ui.heading("Travel & Expenses");
+            let mut changed = false;
+            let mut remove = None;
+            egui::Grid::new("expense_lines").num_columns(5).show(ui, |ui| {
+                for header in ["Description", "Qty", "Unit Cost", "Amount", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (idx, line) in self.project.expenses.iter_mut().enumerate() {
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut line.description)
+                                .hint_text("Trip, per diem…")
+                                .desired_width(200.0),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut line.quantity).speed(0.5).range(0.0..=10_000.0))
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut line.unit_cost)
+                                .speed(5.0)
+                                .range(0.0..=1_000_000.0)
+                                .prefix("$"),
+                        )
+                        .changed();
+                    ui.monospace(format_currency(line.total()));
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(idx);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(idx) = remove {
+                self.project.expenses.remove(idx);
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                if ui.button("➕ Add Expense").clicked() {
+                    self.project.expenses.push(ExpenseLine::default());
+                    changed = true;
+                }
+                let total: f32 = self.project.expenses.iter().map(ExpenseLine::total).sum();
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.strong(format_currency(total));
+                    ui.label("Expenses total");
+                });
+            });
+            if changed {
+                self.mark_dirty();
+            }
+        });
+    }
+
+    /// Header cell that sorts the points table by `column`, flipping direction when it
+    /// is already the active column.
+    fn points_sort_header(&mut self, ui: &mut Ui, label: &str, column: PointsSort) {
+        let arrow = match (self.points_table_sort == column, self.points_table_ascending) {
+            (false, _) => "",
+            (true, true) => " ⏶",
+            (true, false) => " ⏷",
+        };
+        if ui.selectable_label(self.points_table_sort == column, format!("{label}{arrow}")).clicked() {
+            if self.points_table_sort == column {
+                self.points_table_ascending = !self.points_table_ascending;
+            } else {
+                self.points_table_sort = column;
+                self.points_table_ascending = true;
+            }
+        }
+    }
+
+    /// Read-only, sortable list of an equipment's child points for `right_properties`.
+    fn equipment_points_table(&mut self, ui: &mut Ui, equipment_id: u64) {
+        let mut points: Vec<(String, PointKind)> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.parent_id == Some(equipment_id) && o.object_type == ObjectType::Point)
+            .map(|o| (o.name.clone(), o.point_kind))
+            .collect();
+        let kind_rank = |kind: PointKind| POINT_KINDS.iter().position(|k| *k == kind).unwrap_or(usize::MAX);
+        match self.points_table_sort {
+            PointsSort::Name => points.sort_by_key(|(name, _)| name.to_lowercase()),
+            PointsSort::Kind => points.sort_by_key(|(name, kind)| (kind_rank(*kind), name.to_lowercase())),
+        }
+        if !self.points_table_ascending {
+            points.reverse();
+        }
+        egui::CollapsingHeader::new(format!("Points ({})", points.len()))
+            .id_source(("equipment_points_table", equipment_id))
+            .default_open(true)
+            .show(ui, |ui| {
+                if points.is_empty() {
+                    ui.small("No points under this equipment.");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                    egui::Grid::new("equipment_points_grid")
+                        .striped(true)
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            self.points_sort_header(ui, "Name", PointsSort::Name);
+                            self.points_sort_header(ui, "Kind", PointsSort::Kind);
+                            ui.end_row();
+                            for (name, kind) in &points {
+                                ui.label(name);
+                                ui.label(format!("{} {kind:?}", kind.icon()));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+    }
+
+    /// Controller type and license combos for a selected controller in `right_properties`.
+    fn controller_model_combos(&mut self, ui: &mut Ui, controller_id: u64) {
+        let types = self.preferences.controller_type_options();
+        let licenses = self.preferences.controller_license_options();
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == controller_id) else {
+            return;
+        };
+        let mut changed = false;
+        egui::Grid::new(("controller_model", controller_id)).num_columns(2).show(ui, |ui| {
+            ui.label("Controller type");
+            changed |= option_combo(ui, ("controller_type", controller_id), &mut obj.controller_type, &types);
+            ui.end_row();
+            ui.label("License");
+            changed |= option_combo(ui, ("controller_license", controller_id), &mut obj.controller_license, &licenses);
+            ui.end_row();
+        });
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    /// Bid/start/completion inputs for `project_settings_view`, each with an inline hint
+    /// when the text doesn't parse as a date.
+    fn proposal_date_fields(&mut self, ui: &mut Ui) {
+        let proposal = &mut self.project.proposal;
+        let mut changed = false;
+        egui::Grid::new("proposal_dates").num_columns(2).show(ui, |ui| {
+            for (label, value) in [
+                ("Bid date", &mut proposal.bid_date),
+                ("Target start", &mut proposal.target_start_date),
+                ("Target completion", &mut proposal.target_completion_date),
+            ] {
+                ui.label(label);
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(value).hint_text("YYYY-MM-DD").desired_width(120.0))
+                        .changed();
+                    if !value.trim().is_empty() && parse_proposal_date(value).is_none() {
+                        ui.colored_label(Color32::from_rgb(230, 90, 80), "Not a valid date");
+                    }
+                });
+                ui.end_row();
+            }
+        });
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    /// Assumption and exclusion clause lists for `project_settings_view`.
+    fn proposal_clause_fields(&mut self, ui: &mut Ui) {
+        let proposal = &mut self.project.proposal;
+        let prefs = &self.preferences;
+        let mut changed = false;
+        for (title, id, clauses, library) in [
+            ("Assumptions", "proposal_assumptions", &mut proposal.assumptions, &prefs.standard_assumptions),
+            ("Exclusions", "proposal_exclusions", &mut proposal.exclusions, &prefs.standard_exclusions),
+        ] {
+            ui.label(RichText::new(title).strong());
+            changed |= clause_list_editor(ui, id, clauses, library);
+            ui.add_space(6.0);
+        }
+        if changed {
+            self.mark_dirty();
+        }
+    }
+
+    /// Date problems for `ux_health_issues`.
+    fn proposal_date_issues(&self) -> Vec<String> {
+        let proposal = &self.project.proposal;
+        let mut issues = Vec::new();
+        for (label, value) in [
+            ("Bid date", &proposal.bid_date),
+            ("Target start date", &proposal.target_start_date),
+            ("Target completion date", &proposal.target_completion_date),
+        ] {
+            if !value.trim().is_empty() && parse_proposal_date(value).is_none() {
+                issues.push(format!("{label} \"{value}\" is not a valid date"));
+            }
+        }
+        if let (Some(start), Some(end)) = (
+            parse_proposal_date(&proposal.target_start_date),
+            parse_proposal_date(&proposal.target_completion_date),
+        ) {
+            if end < start {
+                issues.push(format!("Target completion {end} is before target start {start}"));
+            }
+        }
+        issues
+    }
+
+    /// Explanation under the hours-override controls in `right_properties`.
+    fn hours_override_hint(&self, ui: &mut Ui, equipment_id: u64) {
+        let Some(equipment) = self.project.objects.iter().find(|o| o.id == equipment_id) else {
+            return;
+        };
+        if !equipment.hours_override || equipment.hours_override_mode != HoursOverrideMode::PointsBased {
+            return;
+        }
+        ui.small(format!(
+            "Per-point override still applies point-kind weights: {:.1} engineering h for this equipment's I/O mix.",
+            self.equipment_hours(equipment_id)[0]
+        ));
+    }
+
+    /// Read-only eng/gfx/cx contribution of the selected equipment for `right_properties`.
+    fn equipment_estimate_line(&self, ui: &mut Ui, equipment_id: u64) {
+        let [eng, gfx, cx] = self.equipment_hours(equipment_id);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Estimate").weak());
+            ui.monospace(format!("Eng {eng:.1} h · Gfx {gfx:.1} h · Cx {cx:.1} h"));
+            ui.strong(format!("= {:.1} h", eng + gfx + cx));
+        });
+    }
+
+    /// Tracks whether the last pointer press landed in the tree panel (`tree_rect`), which
+    /// is what "the tree has focus" means for keyboard navigation.
+    fn update_tree_focus(&mut self, ui: &Ui, tree_rect: egui::Rect) {
+        if let Some(press) = ui.input(|i| {
+            i.pointer
+                .any_pressed()
+                .then(|| i.pointer.press_origin())
+                .flatten()
+        }) {
+            self.tree_focused = tree_rect.contains(press);
+        }
+    }
+
+    /// Up/Down walk the visible rows, Left/Right collapse/expand, Delete removes the
+    /// selected subtree (buildings are kept). Ignored while a text field has focus.
+    fn tree_keyboard_navigation(&mut self, ui: &Ui) {
+        if !self.tree_focused || ui.ctx().memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let (up, down, left, right, delete) = ui.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowLeft),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Delete),
+            )
+        });
+        let order = self.visible_tree_order();
+        let current = self
+            .selected_object
+            .and_then(|id| order.iter().position(|&x| x == id));
+        let step = match (up, down) {
+            (true, false) => Some(current.map_or(0, |idx| idx.saturating_sub(1))),
+            (false, true) => Some(current.map_or(0, |idx| (idx + 1).min(order.len().saturating_sub(1)))),
+            _ => None,
+        };
+        if let Some(&id) = step.and_then(|idx| order.get(idx)) {
+            self.handle_tree_click(id, egui::Modifiers::NONE);
+        }
+        let Some(selected) = self.selected_object else {
+            return;
+        };
+        if left {
+            let has_children = self.project.objects.iter().any(|o| o.parent_id == Some(selected));
+            if has_children && !self.collapsed_tree_nodes.contains(&selected) {
+                self.collapsed_tree_nodes.insert(selected);
+            } else if let Some(parent) = self
+                .project
+                .objects
+                .iter()
+                .find(|o| o.id == selected)
+                .and_then(|o| o.parent_id)
+            {
+                // Already collapsed (or a leaf): hop to the parent like most tree widgets.
+                self.handle_tree_click(parent, egui::Modifiers::NONE);
+            }
+        }
+        if right {
+            self.collapsed_tree_nodes.remove(&selected);
+        }
+        if delete {
+            self.delete_tree_node_from_keyboard(selected, &order);
+        }
+    }
+
+    fn delete_tree_node_from_keyboard(&mut self, id: u64, order: &[u64]) {
+        let Some(obj) = self.project.objects.iter().find(|o| o.id == id) else {
+            return;
+        };
+        if obj.object_type == ObjectType::Building {
+            self.status = "Buildings can't be deleted from the keyboard".to_string();
+            return;
+        }
+        let name = obj.name.clone();
+        let fallback = obj.parent_id;
+        self.push_project_history();
+        self.remove_object_subtree(id);
+        // Prefer the row that was just above; fall back to the parent.
+        let previous = order
+            .iter()
+            .position(|&x| x == id)
+            .and_then(|idx| idx.checked_sub(1))
+            .map(|idx| order[idx])
+            .filter(|prev| self.project.objects.iter().any(|o| o.id == *prev));
+        self.selected_object = previous.or(fallback);
+        self.selected_objects.clear();
+        self.selected_objects.extend(self.selected_object);
+        self.mark_dirty();
+        self.status = format!("Deleted {name}");
+    }
+
+    /// Icon and color for a tree row in `object_node`: equipment is styled by its type,
+    /// everything else keeps `ObjectType::icon` and the normal text color.
+    fn object_style(obj: &BasObject) -> (&'static str, Option<Color32>) {
+        if obj.object_type != ObjectType::Equipment {
+            return (obj.object_type.icon(), None);
+        }
+        match equipment_type_style(&obj.equipment_type) {
+            Some((icon, color)) => (icon, Some(color)),
+            None => (obj.object_type.icon(), None),
+        }
+    }
+
+    /// Glyph drawn inside an overlay token in `drawings_overlay_view`, if its equipment
+    /// type has one.
+    fn overlay_node_glyph(&self, node: &OverlayNode) -> Option<(&'static str, Color32)> {
+        self.project
+            .objects
+            .iter()
+            .find(|o| o.id == node.object_id && o.object_type == ObjectType::Equipment)
+            .and_then(|o| equipment_type_style(&o.equipment_type))
+    }
+
+    /// Opens every branch leading to a search hit while a query is active and puts the
+    /// user's own collapse state back once the query is cleared. Called each frame from
+    /// `left_sidebar`.
+    fn sync_search_expansion(&mut self) {
+        if !self.tree_filter_active() {
+            if let Some(saved) = self.collapse_before_search.take() {
+                self.collapsed_tree_nodes = saved;
+            }
+            return;
+        }
+        if self.collapse_before_search.is_none() {
+            self.collapse_before_search = Some(self.collapsed_tree_nodes.clone());
+        }
+        for id in self.search_match_ancestors() {
+            self.collapsed_tree_nodes.remove(&id);
+        }
+    }
+
+    /// "N matches" next to the tree Search box.
+    fn tree_search_match_count(&self, ui: &mut Ui) {
+        if !self.tree_filter_active() {
+            return;
+        }
+        let count = self.project.objects.iter().filter(|o| self.tree_row_matches(o)).count();
+        let text = match count {
+            0 => "No matches".to_string(),
+            1 => "1 match".to_string(),
+            n => format!("{n} matches"),
+        };
+        ui.small(text);
+    }
+
+    /// Row label for `object_node` with the part matching the search query emphasized.
+    fn highlighted_object_name(&self, ui: &Ui, name: &str) -> egui::text::LayoutJob {
+        let normal = egui::TextFormat {
+            font_id: egui::TextStyle::Body.resolve(ui.style()),
+            color: ui.visuals().text_color(),
+            ..Default::default()
+        };
+        let mut job = egui::text::LayoutJob::default();
+        let Some(range) = match_range(name, self.object_search_query.trim()) else {
+            job.append(name, 0.0, normal);
+            return job;
+        };
+        let emphasized = egui::TextFormat {
+            color: self.accent(),
+            underline: egui::Stroke::new(1.0, self.accent()),
+            ..normal.clone()
+        };
+        job.append(&name[..range.start], 0.0, normal.clone());
+        job.append(&name[range.clone()], 0.0, emphasized);
+        job.append(&name[range.end..], 0.0, normal);
+        job
+    }
+
+    /// Adds one equipment under `parent` bound to `template_name` and syncs its points.
+    fn create_templated_equipment(&mut self, parent: u64, name: &str, template_name: &str) -> u64 {
+        let id = self.project.next_id;
+        self.project.next_id += 1;
+        let equipment_type = self
+            .user_templates
+            .iter()
+            .find(|t| t.name == template_name)
+            .map(|t| t.equipment_type.clone())
+            .unwrap_or_default();
+        self.project.objects.push(BasObject {
+            id,
+            name: name.to_string(),
+            object_type: ObjectType::Equipment,
+            parent_id: Some(parent),
+            equipment_type,
+            template_name: template_name.to_string(),
+            ..Default::default()
+        });
+        self.sync_equipment_from_template(id);
+        id
+    }
+
+    /// Next unused tag for `equipment_type` under the project's tag scheme.
+    fn next_equipment_tag(&self, equipment_type: &str) -> String {
+        let scheme = &self.project.tag_scheme;
+        let prefix = scheme.prefix_for(equipment_type);
+        let highest = self
+            .project
+            .objects
+            .iter()
+            .filter_map(|o| scheme.sequence_of(&prefix, &o.equipment_tag))
+            .max()
+            .unwrap_or(0);
+        scheme.format(&prefix, highest + 1)
+    }
+
+    /// "Add N…" entry for a controller's context menu.
+    fn bulk_equipment_menu_item(&mut self, ui: &mut Ui, controller_id: u64) {
+        if ui.button("➕ Add N Equipment…").clicked() {
+            self.bulk_equipment = Some(BulkEquipmentRequest {
+                controller_id,
+                count: 10,
+                template_name: self.user_templates.first().map(|t| t.name.clone()).unwrap_or_default(),
+                base_name: String::new(),
+            });
+            ui.close_menu();
+        }
+    }
+
+    fn bulk_equipment_dialog(&mut self, ctx: &egui::Context) {
+        let Some(mut request) = self.bulk_equipment.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut create = false;
+        let mut cancel = false;
+        egui::Window::new("Add Equipment")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("bulk_equipment_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Quantity");
+                    ui.add(egui::DragValue::new(&mut request.count).range(1..=500));
+                    ui.end_row();
+                    ui.label("Template");
+                    egui::ComboBox::from_id_source("bulk_equipment_template")
+                        .selected_text(request.template_name.as_str())
+                        .show_ui(ui, |ui| {
+                            for template in self.user_templates.iter().filter(|t| !t.archived) {
+                                ui.selectable_value(&mut request.template_name, template.name.clone(), &template.name);
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Name prefix");
+                    ui.add(egui::TextEdit::singleline(&mut request.base_name).hint_text("Tag is used when empty"));
+                    ui.end_row();
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let ready = !request.template_name.is_empty();
+                    if ui.add_enabled(ready, egui::Button::new(format!("Add {}", request.count))).clicked() {
+                        create = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        if create {
+            self.bulk_equipment = None;
+            self.add_equipment_batch(&request);
+        } else if cancel || !open {
+            self.bulk_equipment = None;
+        } else {
+            self.bulk_equipment = Some(request);
+        }
+    }
+
+    /// Creates `request.count` tagged equipment under the controller as one undo step.
+    fn add_equipment_batch(&mut self, request: &BulkEquipmentRequest) {
+        let Some(equipment_type) = self
+            .user_templates
+            .iter()
+            .find(|t| t.name == request.template_name)
+            .map(|t| t.equipment_type.clone())
+        else {
+            return;
+        };
+        self.push_project_history();
+        for n in 1..=request.count {
+            let tag = self.next_equipment_tag(&equipment_type);
+            let name = match request.base_name.trim() {
+                "" => tag.clone(),
+                base => format!("{base} {n}"),
+            };
+            let id = self.create_templated_equipment(request.controller_id, &name, &request.template_name);
+            if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) {
+                obj.equipment_tag = tag;
+            }
+        }
+        self.collapsed_tree_nodes.remove(&request.controller_id);
+        self.mark_dirty();
+        self.status = format!("Added {} × {}", request.count, request.template_name);
+    }
+
+    fn export_bacnet_points(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("bacnet_points.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let result = bacnet_points_csv(&self.project.objects)
+            .and_then(|csv| std::fs::write(&path, csv).map_err(anyhow::Error::from));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported BACnet points");
+                format!("BACnet points exported: {}", path.display())
+            }
+            Err(err) => format!("BACnet points export failed: {err:#}"),
+        };
+    }
+
+    fn export_bill_of_materials(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("bill_of_materials.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let result = bom_csv(&bill_of_materials(&self.project.objects))
+            .and_then(|csv| std::fs::write(&path, csv).map_err(anyhow::Error::from));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported bill of materials");
+                format!("Bill of materials exported: {}", path.display())
+            }
+            Err(err) => format!("Bill of materials export failed: {err:#}"),
+        };
+    }
+
+    /// Read-only structural check. Unlike `normalize_loaded_project` it removes nothing.
+    fn validate_project(&self) -> Vec<ValidationIssue> {
+        let objects = &self.project.objects;
+        let find = |id: u64| objects.iter().find(|o| o.id == id);
+        let mut issues = Vec::new();
+        for obj in objects {
+            match obj.parent_id.map(|p| (p, find(p))) {
+                Some((_, None)) => issues.push(ValidationIssue::MissingParent { object_id: obj.id }),
+                Some((_, Some(parent))) if !Self::can_reparent_object(obj.object_type, parent.object_type) => {
+                    issues.push(ValidationIssue::WrongParentType { object_id: obj.id })
+                }
+                None if obj.object_type != ObjectType::Building => {
+                    issues.push(ValidationIssue::MissingParent { object_id: obj.id })
+                }
+                _ => {}
+            }
+            if obj.object_type == ObjectType::Equipment && obj.template_name.trim().is_empty() {
+                issues.push(ValidationIssue::NoTemplate { object_id: obj.id });
+            }
+        }
+        for sheet in 0..self.project.drawings.len().max(1) {
+            for (node_idx, node) in self.sheet_overlay_nodes(sheet).iter().enumerate() {
+                if find(node.object_id).is_none() {
+                    issues.push(ValidationIssue::DanglingOverlayNode {
+                        sheet,
+                        node_idx,
+                        object_id: node.object_id,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Tokens of drawing `sheet`; the active sheet lives in the `Project::overlay_*` mirror.
+    fn sheet_overlay_nodes(&self, sheet: usize) -> &[OverlayNode] {
+        if sheet == self.project.active_drawing || self.project.drawings.is_empty() {
+            &self.project.overlay_nodes
+        } else {
+            self.project.drawings.get(sheet).map_or(&[], |s| s.overlay_nodes.as_slice())
+        }
+    }
+
+    fn validation_issue_text(&self, issue: &ValidationIssue) -> String {
+        let name = |id: u64| {
+            self.project
+                .objects
+                .iter()
+                .find(|o| o.id == id)
+                .map_or_else(|| format!("#{id}"), |o| format!("{} {}", o.object_type.icon(), o.name))
+        };
+        match issue {
+            ValidationIssue::MissingParent { object_id } => format!("{} has no valid parent", name(*object_id)),
+            ValidationIssue::WrongParentType { object_id } => {
+                format!("{} sits under the wrong kind of parent", name(*object_id))
+            }
+            ValidationIssue::NoTemplate { object_id } => format!("{} has no template assigned", name(*object_id)),
+            ValidationIssue::DanglingOverlayNode { sheet, object_id, .. } => {
+                let sheet_name = self.project.drawings.get(*sheet).map_or("the drawing", |s| s.name.as_str());
+                format!("Overlay token on {sheet_name} points at deleted object #{object_id}")
+            }
+        }
+    }
+
+    /// First object of a type that may parent `object_id`, used by the "Reattach" fix.
+    fn valid_parent_for(&self, object_id: u64) -> Option<u64> {
+        let child = self.project.objects.iter().find(|o| o.id == object_id)?;
+        self.project
+            .objects
+            .iter()
+            .find(|o| o.id != object_id && Self::can_reparent_object(child.object_type, o.object_type))
+            .map(|o| o.id)
+    }
+
+    fn validation_dialog(&mut self, ctx: &egui::Context) {
+        let Some(issues) = self.validation_report.clone() else {
+            return;
+        };
+        let mut open = true;
+        let mut fix: Option<(ValidationIssue, bool)> = None;
+        let mut select = None;
+        egui::Window::new("Validate Project")
+            .collapsible(false)
+            .default_size([560.0, 420.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if issues.is_empty() {
+                    ui.label("✔ No problems found.");
+                    return;
+                }
+                ui.label(format!("{} issue(s) found. Nothing is changed until you pick a fix.", issues.len()));
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    for (idx, issue) in issues.iter().enumerate() {
+                        ui.push_id(idx, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(self.validation_issue_text(issue));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| match issue {
+                                    ValidationIssue::MissingParent { object_id }
+                                    | ValidationIssue::WrongParentType { object_id } => {
+                                        if ui.small_button("Delete").clicked() {
+                                            fix = Some((issue.clone(), false));
+                                        }
+                                        let target = self.valid_parent_for(*object_id);
+                                        if ui.add_enabled(target.is_some(), egui::Button::new("Reattach").small()).clicked() {
+                                            fix = Some((issue.clone(), true));
+                                        }
+                                    }
+                                    ValidationIssue::NoTemplate { object_id } => {
+                                        if ui.small_button("Select").clicked() {
+                                            select = Some(*object_id);
+                                        }
+                                    }
+                                    ValidationIssue::DanglingOverlayNode { .. } => {
+                                        if ui.small_button("Remove token").clicked() {
+                                            fix = Some((issue.clone(), false));
+                                        }
+                                    }
+                                });
+                            });
+                        });
+                    }
+                });
+            });
+        if !open {
+            self.validation_report = None;
+            return;
+        }
+        if let Some(id) = select {
+            self.handle_tree_click(id, egui::Modifiers::NONE);
+        }
+        if let Some((issue, reattach)) = fix {
+            self.apply_validation_fix(&issue, reattach);
+            self.validation_report = Some(self.validate_project());
+        }
+    }
+
+    fn apply_validation_fix(&mut self, issue: &ValidationIssue, reattach: bool) {
+        match *issue {
+            ValidationIssue::MissingParent { object_id } | ValidationIssue::WrongParentType { object_id } => {
+                self.push_project_history();
+                match self.valid_parent_for(object_id).filter(|_| reattach) {
+                    Some(parent) => self.reparent_object(object_id, parent),
+                    None => self.remove_object_subtree(object_id),
+                }
+            }
+            ValidationIssue::DanglingOverlayNode { sheet, node_idx, .. } => {
+                self.push_overlay_history_as("Removed dangling token");
+                let nodes = if sheet == self.project.active_drawing || self.project.drawings.is_empty() {
+                    &mut self.project.overlay_nodes
+                } else {
+                    &mut self.project.drawings[sheet].overlay_nodes
+                };
+                if node_idx < nodes.len() {
+                    nodes.remove(node_idx);
+                }
+            }
+            ValidationIssue::NoTemplate { .. } => return,
+        }
+        self.mark_dirty();
+    }
+
+    /// Copies the live overlay state back into `project.drawings[active_drawing]`,
+    /// creating the first sheet from it if the set is still empty.
+    fn stash_active_drawing(&mut self) {
+        if self.project.drawings.is_empty() {
+            if self.project.overlay_pdf.is_none() && self.project.overlay_nodes.is_empty() {
+                return;
+            }
+            self.project.drawings.push(DrawingSheet {
+                name: "Drawing 1".to_string(),
+                ..Default::default()
+            });
+            self.project.active_drawing = 0;
+        }
+        let active = self.project.active_drawing.min(self.project.drawings.len() - 1);
+        self.project.active_drawing = active;
+        let sheet = &mut self.project.drawings[active];
+        sheet.pdf_file = self.project.overlay_pdf.clone();
+        sheet.overlay_nodes = self.project.overlay_nodes.clone();
+        sheet.overlay_lines = self.project.overlay_lines.clone();
+        sheet.drawing_scale = self.project.drawing_scale.clone();
+        sheet.view = Some(OverlayView {
+            zoom: self.overlay_zoom,
+            pan: [self.overlay_pan.x, self.overlay_pan.y],
+        });
+        self.drawing_pdf_bytes.resize(self.project.drawings.len(), None);
+        self.drawing_pdf_bytes[active] = self.overlay_pdf_bytes.clone();
+    }
+
+    /// Loads sheet `idx` into the live overlay fields and starts rendering it.
+    fn activate_drawing(&mut self, idx: usize) {
+        if idx >= self.project.drawings.len() {
+            return;
+        }
+        self.stash_active_drawing();
+        self.project.active_drawing = idx;
+        let sheet = self.project.drawings[idx].clone();
+        self.project.overlay_pdf = sheet.pdf_file;
+        self.project.overlay_nodes = sheet.overlay_nodes;
+        self.project.overlay_lines = sheet.overlay_lines;
+        self.project.drawing_scale = sheet.drawing_scale;
+        self.apply_overlay_view(sheet.view);
+        self.overlay_pdf_bytes = self.drawing_pdf_bytes.get(idx).cloned().flatten();
+        self.overlay_page = 0;
+        self.overlay_page_count = 0;
+        self.overlay_selection.clear();
+        self.overlay_highlight = None;
+        // Snapshots hold one sheet's tokens; undoing them onto another sheet would mix sets.
+        self.overlay_undo_stack.clear();
+        self.overlay_redo_stack.clear();
+        self.request_overlay_render();
+    }
+
+    /// Picks a PDF and appends it to the drawing set as a new, active sheet.
+    fn add_drawing(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("PDF", &["pdf"]).pick_file() else {
+            return;
+        };
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                self.status = format!("Could not read drawing: {err}");
+                return;
+            }
+        };
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("drawing");
+        self.stash_active_drawing();
+        self.project.drawings.push(DrawingSheet {
+            name: stem.to_string(),
+            pdf_file: Some(format!("{}.pdf", sanitize_file_stem(stem))),
+            ..Default::default()
+        });
+        let idx = self.project.drawings.len() - 1;
+        self.drawing_pdf_bytes.resize(self.project.drawings.len(), None);
+        self.drawing_pdf_bytes[idx] = Some(bytes);
+        self.activate_drawing(idx);
+        self.mark_dirty();
+        self.status = format!("Added drawing {stem}");
+    }
+
+    /// Drawing selector for the `drawings_overlay_view` header.
+    fn drawing_selector(&mut self, ui: &mut Ui) {
+        let active = self.project.active_drawing;
+        let mut chosen = None;
+        if !self.project.drawings.is_empty() {
+            let current = self
+                .project
+                .drawings
+                .get(active)
+                .map_or("—", |sheet| sheet.name.as_str());
+            egui::ComboBox::from_id_source("drawing_selector")
+                .selected_text(current)
+                .show_ui(ui, |ui| {
+                    for (idx, sheet) in self.project.drawings.iter().enumerate() {
+                        if ui.selectable_label(idx == active, &sheet.name).clicked() {
+                            chosen = Some(idx);
+                        }
+                    }
+                });
+        }
+        if ui.button("➕ Add Drawing").clicked() {
+            self.add_drawing();
+        }
+        if let Some(idx) = chosen.filter(|&idx| idx != active) {
+            self.activate_drawing(idx);
+        }
+    }
+
+    /// Bundle entries for every sheet's PDF, named by sheet index under `assets/drawings/`.
+    fn drawing_assets(&mut self) -> Vec<(String, Vec<u8>)> {
+        self.stash_active_drawing();
+        self.drawing_pdf_bytes
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, bytes)| Some((drawing_asset_name(idx), bytes.clone()?)))
+            .collect()
+    }
+
+    /// Called by `load_project_from_path` once the bundle is open. Older projects with a
+    /// single `overlay_pdf` (already in `overlay_pdf_bytes`) become a one-sheet set.
+    fn restore_drawings(&mut self, mut read_asset: impl FnMut(&str) -> Option<Vec<u8>>) {
+        if self.project.drawings.is_empty() {
+            self.drawing_pdf_bytes.clear();
+            self.stash_active_drawing();
+            return;
+        }
+        self.drawing_pdf_bytes = (0..self.project.drawings.len())
+            .map(|idx| read_asset(&drawing_asset_name(idx)))
+            .collect();
+        let active = self.project.active_drawing.min(self.project.drawings.len() - 1);
+        // Load the live fields straight from the stored sheet; `activate_drawing` would
+        // stash the stale pre-load overlay over it first.
+        self.project.active_drawing = active;
+        let sheet = self.project.drawings[active].clone();
+        self.project.overlay_pdf = sheet.pdf_file;
+        self.project.overlay_nodes = sheet.overlay_nodes;
+        self.project.overlay_lines = sheet.overlay_lines;
+        self.project.drawing_scale = sheet.drawing_scale;
+        self.apply_overlay_view(sheet.view);
+        self.overlay_pdf_bytes = self.drawing_pdf_bytes[active].clone();
+        self.overlay_page = 0;
+        self.request_overlay_render();
+    }
+
+    /// Writes an embedded asset to a chosen path, defaulting to its stored file name.
+    fn export_embedded_asset(&mut self, asset: EmbeddedAsset) {
+        let (bytes, stored_name, fallback, label) = match asset {
+            EmbeddedAsset::OverviewImage => (
+                &self.overview_image_bytes,
+                &self.project.overview_image,
+                "overview.png",
+                "overview image",
+            ),
+            EmbeddedAsset::DrawingPdf => (
+                &self.overlay_pdf_bytes,
+                &self.project.overlay_pdf,
+                "drawing.pdf",
+                "drawing PDF",
+            ),
+        };
+        let Some(bytes) = bytes.clone() else {
+            self.status = format!("This project has no {label} embedded");
+            return;
+        };
+        let file_name = stored_name.clone().unwrap_or_else(|| fallback.to_string());
+        let extension = std::path::Path::new(&file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("bin")
+            .to_string();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(extension.to_uppercase(), &[extension.as_str()])
+            .set_file_name(&file_name)
+            .save_file()
+        else {
+            return;
+        };
+        self.status = match std::fs::write(&path, bytes) {
+            Ok(()) => format!("Exported {label} to {}", path.display()),
+            Err(err) => format!("Could not export {label}: {err}"),
+        };
+    }
+
+    fn apply_overlay_view(&mut self, view: Option<OverlayView>) {
+        let view = view.filter(|v| v.zoom.is_finite() && v.zoom > 0.0);
+        self.overlay_zoom = view.map_or(1.0, |v| v.zoom.clamp(OVERLAY_ZOOM_MIN, OVERLAY_ZOOM_MAX));
+        self.overlay_pan = view.map_or(egui::Vec2::ZERO, |v| egui::vec2(v.pan[0], v.pan[1]));
+    }
+
+    /// Make/Model inputs for equipment in `right_properties`, with suggestions from the
+    /// learned catalog. Committed values are added to the catalog.
+    fn make_model_fields(&mut self, ui: &mut Ui, id: u64) {
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) else {
+            return;
+        };
+        let mut changed = false;
+        let mut learned = false;
+        egui::Grid::new(("make_model", id)).num_columns(2).show(ui, |ui| {
+            for (label, value, catalog) in [
+                ("Make", &mut obj.make, &mut self.preferences.known_makes),
+                ("Model", &mut obj.model, &mut self.preferences.known_models),
+            ] {
+                ui.label(label);
+                let (edited, committed) = autocomplete_field(ui, (label, id), value, catalog);
+                changed |= edited;
+                if committed && !value.trim().is_empty() {
+                    learned |= catalog.insert(value.trim().to_string());
+                }
+                ui.end_row();
+            }
+        });
+        if changed {
+            self.mark_dirty();
+        }
+        if learned {
+            if let Err(err) = self.preferences.save() {
+                tracing::warn!(%err, "could not persist make/model catalog");
+            }
+        }
+    }
+
+    /// Sections `export_proposal_markdown` appends after the system mix.
+    fn proposal_extra_sections(&self) -> String {
+        let mut out = points_summary_markdown(&self.project.objects);
+        out.push('\n');
+        out.push_str(&self.cost_markdown());
+        for (title, clauses) in [
+            ("Assumptions", &self.project.proposal.assumptions),
+            ("Exclusions", &self.project.proposal.exclusions),
+        ] {
+            let section = clauses_markdown(title, clauses);
+            if !section.is_empty() {
+                out.push('\n');
+                out.push_str(&section);
+            }
+        }
+        let clarifications = clarifications_markdown(&self.project.objects);
+        if !clarifications.is_empty() {
+            out.push('\n');
+            out.push_str(&clarifications);
+        }
+        out
+    }
+
+    /// Combined proposal: project header, object mix, then `proposal_extra_sections`.
+    /// Hands off to `export_proposal_per_building` when that mode is chosen.
+    fn export_proposal_markdown(&mut self) {
+        if self.proposal_export_mode == ProposalExportMode::PerBuilding {
+            self.export_proposal_per_building();
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Markdown", &["md"])
+            .set_file_name(format!("{}.md", sanitize_file_stem(&self.project.name)))
+            .save_file()
+        else {
+            return;
+        };
+        let mut out = path
+            .parent()
+            .map(|dir| self.proposal_logo_markdown(dir))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "# {}\n\nRevision {}\n\n## Object Mix\n\n| Type | Count |\n|---|---:|\n",
+            self.project.name, self.project.proposal.revision
+        ));
+        for kind in [ObjectType::Building, ObjectType::Controller, ObjectType::Equipment, ObjectType::Point] {
+            let count = self.project.objects.iter().filter(|o| o.object_type == kind).count();
+            out.push_str(&format!("| {kind:?} | {count} |\n"));
+        }
+        out.push('\n');
+        out.push_str(&self.proposal_extra_sections());
+        self.status = match std::fs::write(&path, out) {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported proposal");
+                format!("Exported proposal to {}", path.display())
+            }
+            Err(err) => format!("Proposal export failed: {err}"),
+        };
+    }
+
+    /// Logo upload/remove controls for the Settings dialog.
+    fn company_logo_settings(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Company logo");
+            self.brand_mark(ui, 32.0);
+            if ui.button("Upload…").clicked() {
+                self.upload_company_logo();
+            }
+            if self.company_logo_bytes.is_some() && ui.button("Remove").clicked() {
+                let _ = std::fs::remove_file(AppPreferences::logo_path());
+                self.company_logo_bytes = None;
+                self.company_logo_texture = None;
+            }
+        });
+    }
+
+    fn upload_company_logo(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Image", &["png", "jpg", "jpeg", "bmp"])
+            .pick_file()
+        else {
+            return;
+        };
+        let result = std::fs::read(&path).map_err(AppIoError::from).and_then(|bytes| {
+            // Reject anything the texture loader can't decode before persisting it.
+            image::load_from_memory(&bytes)
+                .map_err(|err| AppIoError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err)))?;
+            let logo_path = AppPreferences::logo_path();
+            if let Some(parent) = logo_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(logo_path, &bytes)?;
+            Ok(bytes)
+        });
+        match result {
+            Ok(bytes) => {
+                self.company_logo_bytes = Some(bytes);
+                self.company_logo_texture = None;
+                self.status = "Company logo updated".to_string();
+            }
+            Err(err) => self.status = format!("Could not use that logo: {err}"),
+        }
+    }
+
+    /// Decodes the logo into a texture on first use.
+    fn company_logo_texture(&mut self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+        if self.company_logo_texture.is_none() {
+            let decoded = image::load_from_memory(self.company_logo_bytes.as_deref()?).ok()?.to_rgba8();
+            let size = [decoded.width() as usize, decoded.height() as usize];
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, decoded.as_raw());
+            self.company_logo_texture = Some(ctx.load_texture("company_logo", image, egui::TextureOptions::LINEAR));
+        }
+        self.company_logo_texture.clone()
+    }
+
+    /// Logo scaled to `height` for the login card and title bar, or the "M8" mark.
+    fn brand_mark(&mut self, ui: &mut Ui, height: f32) {
+        match self.company_logo_texture(ui.ctx()) {
+            Some(texture) => {
+                let size = texture.size_vec2();
+                let width = size.x * height / size.y.max(1.0);
+                ui.image((texture.id(), egui::vec2(width, height)));
+            }
+            None => {
+                ui.label(RichText::new("M8").strong().size(height * 0.7).color(self.accent()));
+            }
+        }
+    }
+
+    /// Copies the logo next to a Markdown proposal written into `export_dir` and returns
+    /// the image line for the top of the document; empty when no logo is set.
+    fn proposal_logo_markdown(&self, export_dir: &std::path::Path) -> String {
+        let Some(bytes) = &self.company_logo_bytes else {
+            return String::new();
+        };
+        let extension = image::guess_format(bytes)
+            .ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("png");
+        let file_name = format!("company_logo.{extension}");
+        match std::fs::write(export_dir.join(&file_name), bytes) {
+            Ok(()) => format!("![{}]({file_name})\n\n", self.project.settings.company_name),
+            Err(err) => {
+                tracing::warn!(%err, "could not write logo beside proposal");
+                String::new()
+            }
+        }
+    }
+
+    /// Compact save-state label for the bottom status panel. Amber once changes have
+    /// gone unsaved for longer than the autosave interval.
+    fn save_state_indicator(&self, ui: &mut Ui) {
+        let interval = Duration::from_secs(u64::from(self.preferences.autosave_minutes.max(1)) * 60);
+        let since_save = self.last_autosave_at.map(|at| at.elapsed());
+        let (text, color) = if self.dirty {
+            let overdue = since_save.map_or(true, |elapsed| elapsed > interval);
+            let color = if overdue {
+                Color32::from_rgb(235, 170, 60)
+            } else {
+                ui.visuals().text_color()
+            };
+            ("● Unsaved changes".to_string(), color)
+        } else {
+            let text = match since_save {
+                Some(elapsed) => format!("✔ Saved {}", format_elapsed(elapsed)),
+                None => "✔ Saved".to_string(),
+            };
+            (text, ui.visuals().weak_text_color())
+        };
+        let response = ui.label(RichText::new(text).small().color(color));
+        if let Some(elapsed) = since_save {
+            response.on_hover_text(format!("Last autosave {}", format_elapsed(elapsed)));
+        }
+    }
+
+    /// Controller context-menu entry that drops every child equipment not yet on the
+    /// drawing onto the current page in a grid near the origin, as one undo step.
+    fn place_controller_equipment_menu_item(&mut self, ui: &mut Ui, controller_id: u64) {
+        if ui.button("📍 Place all equipment on drawing").clicked() {
+            self.place_controller_equipment(controller_id);
+            ui.close_menu();
+        }
+    }
+
+    fn place_controller_equipment(&mut self, controller_id: u64) {
+        const COLUMNS: usize = 8;
+        let spacing = self.preferences.overlay_grid_step.max(1.0) * 2.0;
+        let origin = spacing;
+        let unplaced: Vec<u64> = self
+            .project
+            .objects
             .iter()
-            .any(|o| o.object_type == ObjectType::Equipment);
+            .filter(|o| o.parent_id == Some(controller_id) && o.object_type == ObjectType::Equipment)
+            .filter(|o| !self.project.overlay_nodes.iter().any(|n| n.object_id == o.id))
+            .map(|o| o.id)
+            .collect();
+        if unplaced.is_empty() {
+            self.status = "Every equipment on this controller is already on the drawing".to_string();
+            return;
+        }
+        self.push_overlay_history_as("Placed equipment");
+        for (i, object_id) in unplaced.iter().enumerate() {
+            self.project.overlay_nodes.push(OverlayNode {
+                object_id: *object_id,
+                x: origin + (i % COLUMNS) as f32 * spacing,
+                y: origin + (i / COLUMNS) as f32 * spacing,
+                page: self.overlay_page,
+                ..Default::default()
+            });
+        }
+        self.mark_dirty();
+        self.status = format!("Placed {} equipment near the drawing origin", unplaced.len());
+    }
 
-        Self::card_frame().show(ui, |ui| {
-            ui.label(RichText::new("Core Capabilities").strong());
-            ui.separator();
-            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
-            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
-            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
+    /// Route-tool endpoint for a click: the center of a token under the cursor (with its
+    /// object id, so the wire follows the token), otherwise the free placement point.
+    fn wire_endpoint(&self, canvas_min: egui::Pos2, pos: egui::Pos2) -> ([f32; 2], Option<u64>) {
+        if self.wire_snap_to_nodes {
+            if let Some(OverlayTarget::Node(idx)) = self.overlay_hit_test(canvas_min, pos) {
+                let node = &self.project.overlay_nodes[idx];
+                return ([node.x, node.y], Some(node.object_id));
+            }
+        }
+        (self.overlay_placement_point(canvas_min, pos), None)
+    }
+
+    /// Moves attached wire ends onto their tokens' current centers.
+    fn follow_wire_ends(&mut self) {
+        let nodes = &self.project.overlay_nodes;
+        let center = |object_id: Option<u64>, page: usize| {
+            let object_id = object_id?;
+            nodes
+                .iter()
+                .find(|n| n.object_id == object_id && n.page == page)
+                .map(|n| [n.x, n.y])
+        };
+        for line in &mut self.project.overlay_lines {
+            if let Some(from) = center(line.from_object, line.page) {
+                line.from = from;
+            }
+            if let Some(to) = center(line.to_object, line.page) {
+                line.to = to;
+            }
+        }
+    }
+
+    /// "Snap wires to tokens" toggle for the Route tool toolbar.
+    fn wire_snap_toggle(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.wire_snap_to_nodes, "Snap wires to tokens")
+            .on_hover_text("Clicking a token connects the wire to its center and keeps it attached");
+    }
+
+    fn export_wiring_report(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("wiring_report.csv")
+            .save_file()
+        else {
+            return;
+        };
+        let result = wiring_report_csv(
+            &self.project.objects,
+            &self.project.overlay_lines,
+            self.project.drawing_scale.as_ref(),
+        )
+        .and_then(|csv| std::fs::write(&path, csv).map_err(anyhow::Error::from));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported wiring report");
+                format!("Wiring report exported: {}", path.display())
+            }
+            Err(err) => format!("Wiring report export failed: {err:#}"),
+        };
+    }
+
+    /// Records the overlay before an edit, labeled for the Edit History panel.
+    fn push_overlay_history_as(&mut self, label: &'static str) {
+        self.overlay_undo_stack.push((label, self.overlay_snapshot()));
+        if self.overlay_undo_stack.len() > PROJECT_HISTORY_LIMIT {
+            self.overlay_undo_stack.remove(0);
+        }
+        self.overlay_redo_stack.clear();
+    }
+
+    fn overlay_snapshot(&self) -> OverlaySnapshot {
+        OverlaySnapshot {
+            nodes: self.project.overlay_nodes.clone(),
+            lines: self.project.overlay_lines.clone(),
+        }
+    }
+
+    fn restore_overlay_snapshot(&mut self, snapshot: OverlaySnapshot) {
+        self.project.overlay_nodes = snapshot.nodes;
+        self.project.overlay_lines = snapshot.lines;
+        self.overlay_selection.clear();
+        self.mark_dirty();
+    }
+
+    fn overlay_undo(&mut self) {
+        let Some((label, snapshot)) = self.overlay_undo_stack.pop() else {
+            return;
+        };
+        self.overlay_redo_stack.push((label, self.overlay_snapshot()));
+        self.restore_overlay_snapshot(snapshot);
+        self.status = format!("Undid {}", label.to_lowercase());
+    }
+
+    fn overlay_redo(&mut self) {
+        let Some((label, snapshot)) = self.overlay_redo_stack.pop() else {
+            return;
+        };
+        self.overlay_undo_stack.push((label, self.overlay_snapshot()));
+        self.restore_overlay_snapshot(snapshot);
+        self.status = format!("Redid {}", label.to_lowercase());
+    }
+
+    /// Edit History panel for the overlay: past edits oldest first, the current state,
+    /// then undone edits. Clicking a row undoes or redoes step by step up to it, so the
+    /// redo stack stays consistent.
+    fn overlay_history_panel(&mut self, ui: &mut Ui) {
+        let undo: Vec<&'static str> = self.overlay_undo_stack.iter().map(|(label, _)| *label).collect();
+        let redo: Vec<&'static str> = self.overlay_redo_stack.iter().map(|(label, _)| *label).collect();
+        let mut undo_steps = 0;
+        let mut redo_steps = 0;
+        egui::CollapsingHeader::new(format!("Edit History ({}/{PROJECT_HISTORY_LIMIT})", undo.len()))
+            .id_source("overlay_history")
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+                    for (idx, label) in undo.iter().enumerate() {
+                        if ui.selectable_label(false, format!("↶ {label}")).clicked() {
+                            // Jumping to the state before edit `idx` undoes it and everything after.
+                            undo_steps = undo.len() - idx;
+                        }
+                    }
+                    let _ = ui.selectable_label(true, "● Current");
+                    for (idx, label) in redo.iter().rev().enumerate() {
+                        if ui.selectable_label(false, RichText::new(format!("↷ {label}")).weak()).clicked() {
+                            redo_steps = idx + 1;
+                        }
+                    }
+                });
+            });
+        for _ in 0..undo_steps {
+            self.overlay_undo();
+        }
+        for _ in 0..redo_steps {
+            self.overlay_redo();
+        }
+    }
+
+    /// Multiline notes box for the selected object in `right_properties`.
+    fn object_notes_editor(&mut self, ui: &mut Ui, id: u64) {
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) else {
+            return;
+        };
+        ui.label("Notes");
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut obj.notes)
+                .id_source(("object_notes", id))
+                .hint_text("e.g. field verify duct size")
+                .desired_rows(3)
+                .desired_width(f32::INFINITY),
+        );
+        if response.changed() {
+            self.mark_dirty();
+        }
+    }
+
+    /// Status glyphs drawn after a tree row's name in `object_node`, each with its
+    /// hover text.
+    fn object_row_markers(obj: &BasObject) -> Vec<(&'static str, String)> {
+        let mut markers = Vec::new();
+        if obj.object_type == ObjectType::Equipment && obj.template_name.trim().is_empty() {
+            markers.push(("⚠", "No template assigned; contributes no estimate hours".to_string()));
+        }
+        let notes = obj.notes.trim();
+        if !notes.is_empty() {
+            markers.push(("📝", notes.to_string()));
+        }
+        markers
+    }
+
+    /// Type toggles and the point-kind combo above the tree in `left_sidebar`.
+    fn tree_type_filter_bar(&mut self, ui: &mut Ui) {
+        let filter = &mut self.tree_type_filter;
+        ui.horizontal_wrapped(|ui| {
+            ui.toggle_value(&mut filter.buildings, format!("{} Buildings", ObjectType::Building.icon()));
+            ui.toggle_value(&mut filter.controllers, format!("{} Controllers", ObjectType::Controller.icon()));
+            ui.toggle_value(&mut filter.equipment, format!("{} Equipment", ObjectType::Equipment.icon()));
+            ui.toggle_value(&mut filter.points, format!("{} Points", ObjectType::Point.icon()));
+            ui.add_enabled_ui(filter.points, |ui| {
+                egui::ComboBox::from_id_source("tree_point_kind_filter")
+                    .selected_text(filter.point_kind.map_or("All kinds".to_string(), |k| format!("{k:?}")))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut filter.point_kind, None, "All kinds");
+                        for kind in POINT_KINDS {
+                            ui.selectable_value(&mut filter.point_kind, Some(kind), format!("{} {kind:?}", kind.icon()));
+                        }
+                    });
+            });
+            if filter.is_active() && ui.small_button("Reset").clicked() {
+                *filter = TreeTypeFilter::default();
+            }
+        });
+    }
+
+    /// Equipment with no template, for `ux_health_issues`. These add nothing to
+    /// `estimate_hours`, so the bid is silently low.
+    fn untemplated_equipment_issues(&self) -> Vec<String> {
+        self.project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Equipment && o.template_name.trim().is_empty())
+            .map(|o| format!("{} has no template assigned and adds no estimate hours", o.name))
+            .collect()
+    }
+
+    /// First active template whose equipment type matches, ignoring case.
+    fn default_template_for(&self, equipment_type: &str) -> Option<String> {
+        let equipment_type = equipment_type.trim();
+        if equipment_type.is_empty() {
+            return None;
+        }
+        self.user_templates
+            .iter()
+            .find(|t| !t.archived && t.equipment_type.trim().eq_ignore_ascii_case(equipment_type))
+            .map(|t| t.name.clone())
+    }
+
+    /// Assigns every untemplated equipment the default template for its type. Returns
+    /// how many were assigned; the rest have no matching template.
+    fn assign_default_templates(&mut self) -> usize {
+        let assignments: Vec<(u64, String)> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Equipment && o.template_name.trim().is_empty())
+            .filter_map(|o| Some((o.id, self.default_template_for(&o.equipment_type)?)))
+            .collect();
+        if assignments.is_empty() {
+            return 0;
+        }
+        self.push_project_history();
+        for (id, template_name) in &assignments {
+            if let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == *id) {
+                obj.template_name = template_name.clone();
+            }
+            self.sync_equipment_from_template(*id);
+        }
+        self.mark_dirty();
+        assignments.len()
+    }
+
+    /// One-click fix shown beside the untemplated-equipment issues in the health panel.
+    fn untemplated_equipment_fix(&mut self, ui: &mut Ui) {
+        let missing = self.untemplated_equipment_issues().len();
+        if missing == 0 {
+            return;
+        }
+        if ui
+            .button("Assign default template by type")
+            .on_hover_text("Match each equipment's type against the available templates")
+            .clicked()
+        {
+            let assigned = self.assign_default_templates();
+            self.status = match missing - assigned {
+                0 => format!("Assigned templates to {assigned} equipment"),
+                left => format!("Assigned templates to {assigned} equipment; {left} have no matching template"),
+            };
+        }
+    }
+
+    /// Insert or Cmd+Enter adds the natural child of the selected object, the same as
+    /// its context menu, and selects it. Ignored while a text field has focus so
+    /// Cmd+Enter in the notes box doesn't create objects.
+    fn handle_add_child_shortcut(&mut self, ctx: &egui::Context) {
+        if self.app_screen != AppScreen::Studio || ctx.memory(|m| m.focused().is_some()) {
+            return;
+        }
+        let Some(parent) = self
+            .selected_object
+            .and_then(|id| self.project.objects.iter().find(|o| o.id == id))
+        else {
+            return;
+        };
+        let Some(child_type) = Self::child_object_type(parent.object_type) else {
+            return;
+        };
+        let parent_id = parent.id;
+        let add_child = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+        let pressed = ctx.input_mut(|i| {
+            i.consume_shortcut(&add_child) || i.consume_key(egui::Modifiers::NONE, egui::Key::Insert)
+        });
+        if !pressed {
+            return;
+        }
+        self.push_project_history();
+        let id = self.project.next_id;
+        self.project.next_id += 1;
+        let name = format!("New {child_type:?}");
+        self.project.objects.push(BasObject {
+            id,
+            name: name.clone(),
+            object_type: child_type,
+            parent_id: Some(parent_id),
+            ..Default::default()
+        });
+        self.collapsed_tree_nodes.remove(&parent_id);
+        self.handle_tree_click(id, egui::Modifiers::NONE);
+        self.mark_dirty();
+        self.status = format!("Added {name}");
+    }
+
+    /// `root` and all of its descendants, root first, in project order.
+    fn subtree_objects(&self, root: u64) -> Vec<&BasObject> {
+        let mut ids = vec![root];
+        let mut i = 0;
+        while i < ids.len() {
+            let parent = ids[i];
+            ids.extend(self.project.objects.iter().filter(|o| o.parent_id == Some(parent)).map(|o| o.id));
+            i += 1;
+        }
+        ids.iter()
+            .filter_map(|id| self.project.objects.iter().find(|o| o.id == *id))
+            .collect()
+    }
+
+    fn copy_subtree(&mut self, ctx: &egui::Context, root: u64) {
+        let objects = self.subtree_objects(root);
+        let Some(name) = objects.first().map(|o| o.name.clone()) else {
+            return;
+        };
+        let remap: HashMap<u64, u64> = objects.iter().zip(1..).map(|(o, new)| (o.id, new)).collect();
+        let objects: Vec<BasObject> = objects
+            .into_iter()
+            .map(|o| BasObject {
+                id: remap[&o.id],
+                parent_id: o.parent_id.and_then(|p| remap.get(&p).copied()),
+                ..o.clone()
+            })
+            .collect();
+        let count = objects.len();
+        let payload = SubtreeClipboard {
+            format: SUBTREE_CLIPBOARD_FORMAT.to_string(),
+            objects,
+        };
+        self.status = match serde_json::to_string_pretty(&payload) {
+            Ok(json) => {
+                ctx.copy_text(json);
+                format!("Copied {name} ({count} objects)")
+            }
+            Err(err) => format!("Copy failed: {err}"),
+        };
+    }
+
+    /// Parses a "Copy Subtree" payload and checks its root can live under `parent`.
+    fn parse_subtree_clipboard(&self, text: &str, parent: u64) -> Result<Vec<BasObject>, String> {
+        let payload: SubtreeClipboard =
+            serde_json::from_str(text).map_err(|_| "Clipboard does not contain a copied subtree".to_string())?;
+        if payload.format != SUBTREE_CLIPBOARD_FORMAT {
+            return Err(format!("Unsupported clipboard format {}", payload.format));
+        }
+        let root = payload
+            .objects
+            .first()
+            .filter(|o| o.parent_id.is_none())
+            .ok_or("Copied subtree is empty")?;
+        let parent_type = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == parent)
+            .map(|o| o.object_type)
+            .ok_or("Select a parent to paste under")?;
+        if !Self::can_reparent_object(root.object_type, parent_type) {
+            return Err(format!("A {:?} can't be placed under a {parent_type:?}", root.object_type));
+        }
+        Ok(payload.objects)
+    }
+
+    /// Pastes a copied subtree from clipboard `text` under `parent` with fresh ids from
+    /// `next_id`.
+    fn paste_subtree(&mut self, parent: u64, text: &str) {
+        let objects = match self.parse_subtree_clipboard(text, parent) {
+            Ok(objects) => objects,
+            Err(message) => {
+                self.status = message;
+                return;
+            }
+        };
+        self.push_project_history();
+        let mut remap = HashMap::new();
+        for obj in &objects {
+            remap.insert(obj.id, self.project.next_id);
+            self.project.next_id += 1;
+        }
+        let root = remap[&objects[0].id];
+        let name = objects[0].name.clone();
+        let count = objects.len();
+        for obj in objects {
+            // Anything whose parent isn't in the payload hangs off the paste target.
+            let parent_id = obj.parent_id.and_then(|p| remap.get(&p).copied()).unwrap_or(parent);
+            self.project.objects.push(BasObject {
+                id: remap[&obj.id],
+                parent_id: Some(parent_id),
+                ..obj
+            });
+        }
+        self.collapsed_tree_nodes.remove(&parent);
+        self.handle_tree_click(root, egui::Modifiers::NONE);
+        self.mark_dirty();
+        self.status = format!("Pasted {name} ({count} objects)");
+    }
+
+    /// "Copy Subtree"/"Paste Subtree" entries for the `object_node` context menu.
+    fn subtree_clipboard_menu_items(&mut self, ui: &mut Ui, id: u64) {
+        if ui.button("📋 Copy Subtree").clicked() {
+            self.copy_subtree(ui.ctx(), id);
+            ui.close_menu();
+        }
+        if ui.button("📥 Paste Subtree").clicked() {
+            // egui only hands over clipboard text with the platform paste event, so the
+            // menu arms the target and the next Cmd+V delivers the payload.
+            self.subtree_paste_target = Some(id);
+            self.status = "Press Cmd+V to paste the copied subtree".to_string();
+            ui.close_menu();
+        }
+    }
+
+    /// Cmd+V in the tree pastes under the armed "Paste Subtree" target, or the selected
+    /// object. Left alone while a text field has focus so normal pasting still works.
+    fn handle_subtree_paste(&mut self, ui: &Ui) {
+        if ui.ctx().memory(|m| m.focused().is_some()) {
+            return;
+        }
+        if !self.tree_focused && self.subtree_paste_target.is_none() {
+            return;
+        }
+        let Some(text) = ui.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            })
+        }) else {
+            return;
+        };
+        match self.subtree_paste_target.take().or(self.selected_object) {
+            Some(parent) => self.paste_subtree(parent, &text),
+            None => self.status = "Select a parent to paste under".to_string(),
+        }
+    }
+
+    /// Plain pretty-printed project for diffing and debugging: no zip, no obfuscation,
+    /// and no embedded drawing or overview assets.
+    fn export_project_json(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name(format!("{}.json", sanitize_file_stem(&self.project.name)))
+            .save_file()
+        else {
+            return;
+        };
+        // Fold the live overlay back into its sheet so `drawings` is current.
+        self.stash_active_drawing();
+        let result = serde_json::to_vec_pretty(&self.project)
+            .map_err(AppIoError::from)
+            .and_then(|json| std::fs::write(&path, json).map_err(AppIoError::from));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "exported project JSON");
+                format!("Project exported as JSON (assets not included): {}", path.display())
+            }
+            Err(err) => format!("Project JSON export failed: {err}"),
+        };
+    }
+
+    fn import_project_json(&mut self) {
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+        let result = std::fs::read(&path)
+            .map_err(AppIoError::from)
+            .and_then(|bytes| serde_json::from_slice::<Project>(&bytes).map_err(AppIoError::from));
+        let mut project = match result {
+            Ok(project) => project,
+            Err(err) => {
+                self.status = format!("Project JSON import failed: {err}");
+                return;
+            }
+        };
+        migrate_project(&mut project);
+        self.project = project;
+        self.normalize_loaded_project();
+        self.overview_image_bytes = None;
+        self.overlay_pdf_bytes = None;
+        self.restore_drawings(|_| None);
+        self.project_undo.clear();
+        self.project_redo.clear();
+        self.overlay_undo_stack.clear();
+        self.overlay_redo_stack.clear();
+        self.overlay_selection.clear();
+        // A JSON import is not a project file; the next Save should ask where it goes.
+        self.project_path = None;
+        self.mark_dirty();
+        tracing::info!(path = %path.display(), "imported project JSON");
+        self.status = format!(
+            "Imported {} from JSON; drawings and images are not included and must be re-attached",
+            self.project.name
+        );
+    }
+
+    /// Clickable per-type count in the status bar: narrows the tree to that type, or
+    /// clears the filter when it is already the one applied.
+    fn status_count_filter(&mut self, ui: &mut Ui, kind: ObjectType, count: usize) {
+        let filter = TreeTypeFilter::only(kind);
+        let active = self.tree_type_filter == filter;
+        let response = ui
+            .selectable_label(active, format!("{} {}", kind.icon(), count))
+            .on_hover_text(if active {
+                "Show all object types in the tree".to_string()
+            } else {
+                format!("Show only {kind:?} objects in the tree")
+            });
+        if response.clicked() {
+            self.tree_type_filter = if active { TreeTypeFilter::default() } else { filter };
+            self.tree_focused = true;
+            self.tree_scroll_to_match = !active;
+        }
+    }
+
+    fn poll_startup_preload(&mut self) {
+        let Some(rx) = &self.startup_preload_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(preload) => {
+                // Seeding waits for this, but templates imported in the meantime are
+                // kept: the stored library is merged in by name and wins on a clash.
+                let added_meanwhile = !self.user_templates.is_empty() || !self.controller_templates.is_empty();
+                let mut user_templates = preload.user_templates;
+                for template in std::mem::take(&mut self.user_templates) {
+                    if !user_templates.iter().any(|t| t.name == template.name) {
+                        user_templates.push(template);
+                    }
+                }
+                let mut controller_templates = preload.controller_templates;
+                for template in std::mem::take(&mut self.controller_templates) {
+                    if !controller_templates.iter().any(|t| t.name == template.name) {
+                        controller_templates.push(template);
+                    }
+                }
+                self.user_templates = user_templates;
+                self.controller_templates = controller_templates;
+                self.recovery_candidates = preload.recovery_candidates;
+                self.startup_preload_rx = None;
+                self.migrate_archived_templates();
+                if added_meanwhile {
+                    self.save_user_templates();
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.startup_preload_rx = None,
+        }
+    }
+
+    /// Logo card shown in the small startup window while `StartupPreload` runs; moves
+    /// on to the login card once `splash_finished` says so.
+    fn splash_screen(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default()
+            .frame(Self::surface_panel().inner_margin(egui::Margin::same(16.0)))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(36.0);
+                    self.brand_mark(ui, 56.0);
+                    ui.heading("AutoMate");
+                    ui.add_space(8.0);
+                    self.splash_progress_hint(ui);
+                });
+            });
+        if self.splash_finished(ctx) {
+            self.app_screen = AppScreen::Login;
+        }
+    }
+
+    /// Called each frame by `splash_screen`: true once the splash has shown for
+    /// `SPLASH_MIN_DURATION` and either loading finished, the user pressed a key or
+    /// clicked, or `SPLASH_MAX_DURATION` ran out. Loading keeps going in the background
+    /// if the splash is skipped.
+    fn splash_finished(&self, ctx: &egui::Context) -> bool {
+        let elapsed = self.splash_started.elapsed();
+        if elapsed < SPLASH_MIN_DURATION {
+            ctx.request_repaint_after(SPLASH_MIN_DURATION - elapsed);
+            return false;
+        }
+        let skipped = ctx.input(|i| {
+            i.pointer.any_click()
+                || i.events
+                    .iter()
+                    .any(|e| matches!(e, egui::Event::Key { pressed: true, .. }))
+        });
+        if skipped || self.startup_preload_rx.is_none() || elapsed >= SPLASH_MAX_DURATION {
+            return true;
+        }
+        ctx.request_repaint_after(Duration::from_millis(50));
+        false
+    }
+
+    /// Small progress line under the splash logo.
+    fn splash_progress_hint(&self, ui: &mut Ui) {
+        let text = if self.startup_preload_rx.is_some() {
+            "Loading templates and settings…"
+        } else {
+            "Ready — press any key"
+        };
+        ui.small(RichText::new(text).weak());
+    }
+
+    /// Combined / per-building choice for the proposal export, in `project_settings_view`.
+    fn proposal_export_mode_picker(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Export");
+            ui.selectable_value(&mut self.proposal_export_mode, ProposalExportMode::Combined, "Combined");
+            ui.selectable_value(&mut self.proposal_export_mode, ProposalExportMode::PerBuilding, "Per building")
+                .on_hover_text("One Markdown file per building, estimated from that building's subtree only");
+        });
+    }
+
+    /// Markdown for one building: its object mix, point summary, and the equipment
+    /// hours from its subtree. Custom hour lines and overhead are project-wide and are
+    /// left out.
+    fn building_proposal_markdown(&self, building_id: u64) -> Option<String> {
+        let subtree: Vec<BasObject> = self.subtree_objects(building_id).into_iter().cloned().collect();
+        let building = subtree.first().filter(|b| b.object_type == ObjectType::Building)?;
+        let mut out = format!(
+            "# {} — {}\n\nRevision {}\n\n## Object Mix\n\n| Type | Count |\n|---|---:|\n",
+            self.project.name, building.name, self.project.proposal.revision
+        );
+        for kind in [ObjectType::Controller, ObjectType::Equipment, ObjectType::Point] {
+            let count = subtree.iter().filter(|o| o.object_type == kind).count();
+            out.push_str(&format!("| {kind:?} | {count} |\n"));
+        }
+        out.push('\n');
+        out.push_str(&points_summary_markdown(&subtree));
+        let (buildings, _) = self.estimate_breakdown();
+        let hours = buildings.into_iter().find(|r| r.id == building_id).unwrap_or_default();
+        out.push_str("\n## Estimated Hours\n\n| Category | Hours |\n|---|---:|\n");
+        for (label, value) in [
+            ("Engineering", hours.engineering),
+            ("Graphics", hours.graphics),
+            ("Commissioning", hours.commissioning),
+        ] {
+            out.push_str(&format!("| {label} | {value:.1} |\n"));
+        }
+        out.push_str(&format!("| **Total** | **{:.1}** |\n", hours.total()));
+        out.push_str("\n_Project-wide custom hours and QA/PM/risk overhead are not included._\n");
+        let clarifications = clarifications_markdown(&subtree);
+        if !clarifications.is_empty() {
+            out.push('\n');
+            out.push_str(&clarifications);
+        }
+        Some(out)
+    }
+
+    /// Per-building branch of the proposal export: writes `<project> - <building>.md`
+    /// for every building into a chosen folder.
+    fn export_proposal_per_building(&mut self) {
+        let buildings: Vec<(u64, String)> = self
+            .project
+            .objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Building)
+            .map(|o| (o.id, o.name.clone()))
+            .collect();
+        if buildings.is_empty() {
+            self.status = "No buildings to export".to_string();
+            return;
+        }
+        let Some(dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        let project = sanitize_file_stem(&self.project.name);
+        let result = buildings.iter().try_for_each(|(id, name)| {
+            let Some(markdown) = self.building_proposal_markdown(*id) else {
+                return Ok(());
+            };
+            let path = dir.join(format!("{project} - {}.md", sanitize_file_stem(name)));
+            std::fs::write(path, markdown)
         });
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(dir = %dir.display(), count = buildings.len(), "exported per-building proposals");
+                format!("Exported {} building proposal(s) to {}", buildings.len(), dir.display())
+            }
+            Err(err) => format!("Per-building proposal export failed: {err}"),
+        };
+    }
+
+    /// Templates a preset carries: the ones ticked for export, or failing that every
+    /// template the project's equipment uses.
+    fn preset_templates(&self) -> Vec<EquipmentTemplate> {
+        let used: HashSet<&str> = self.project.objects.iter().map(|o| o.template_name.as_str()).collect();
+        self.user_templates
+            .iter()
+            .filter(|t| {
+                if self.template_export_selection.is_empty() {
+                    used.contains(t.name.as_str())
+                } else {
+                    self.template_export_selection.contains(&t.name)
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn save_project_preset(&mut self) {
+        let dir = ProjectPreset::presets_dir();
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Preset", &["json"])
+            .set_directory(&dir)
+            .set_file_name(format!("{}.json", sanitize_file_stem(&self.project.name)))
+            .save_file()
+        else {
+            return;
+        };
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let preset = ProjectPreset {
+            name: name.clone(),
+            estimator: self.project.estimator.clone(),
+            custom_hour_lines: self.project.custom_hour_lines.clone(),
+            assumptions: self.project.proposal.assumptions.clone(),
+            exclusions: self.project.proposal.exclusions.clone(),
+            templates: self.preset_templates(),
+        };
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(AppIoError::from)
+            .and_then(|()| Ok(serde_json::to_vec_pretty(&preset)?))
+            .and_then(|json| Ok(std::fs::write(&path, json)?));
+        self.status = match result {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "saved project preset");
+                format!("Saved preset {name} with {} template(s)", preset.templates.len())
+            }
+            Err(err) => format!("Could not save preset: {err}"),
+        };
+    }
+
+    /// "New from Preset": a fresh project seeded with the preset's estimator settings,
+    /// custom hour lines and proposal clauses. Preset templates missing from the local
+    /// library are added to it; same-named local templates are left alone.
+    fn new_project_from_preset(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Preset", &["json"])
+            .set_directory(ProjectPreset::presets_dir())
+            .pick_file()
+        else {
+            return;
+        };
+        let result = std::fs::read(&path)
+            .map_err(AppIoError::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<ProjectPreset>(&bytes)?));
+        let preset = match result {
+            Ok(preset) => preset,
+            Err(err) => {
+                self.status = format!("Could not open preset: {err}");
+                return;
+            }
+        };
+        self.new_project();
+        self.project.estimator = preset.estimator;
+        self.project.custom_hour_lines = preset.custom_hour_lines;
+        self.project.proposal.assumptions = preset.assumptions;
+        self.project.proposal.exclusions = preset.exclusions;
+        let mut added = 0;
+        for template in preset.templates {
+            if !self.user_templates.iter().any(|t| t.name == template.name) {
+                self.user_templates.push(template);
+                added += 1;
+            }
+        }
+        if added > 0 {
+            self.save_user_templates();
+        }
+        self.mark_clean();
+        self.status = format!("New project from preset {} ({added} template(s) added)", preset.name);
+    }
+
+    /// Editor for `selected_object` in the properties panel; sections below the common
+    /// fields depend on the object type.
+    fn right_properties(&mut self, ui: &mut Ui) {
+        ui.heading("Properties");
+        ui.separator();
+        if self.multi_selection_summary(ui) {
+            return;
+        }
+        let Some(id) = self.selected_object else {
+            ui.small("Select an object in the tree to edit it.");
+            return;
+        };
+        let Some(obj) = self.project.objects.iter_mut().find(|o| o.id == id) else {
+            self.selected_object = None;
+            return;
+        };
+        let object_type = obj.object_type;
+        let mut changed = false;
+        egui::Grid::new("object_properties")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Type");
+                ui.label(RichText::new(format!("{object_type:?}")).weak());
+                ui.end_row();
+                ui.label("Name");
+                changed |= ui.text_edit_singleline(&mut obj.name).changed();
+                ui.end_row();
+                if object_type == ObjectType::Equipment {
+                    ui.label("Hours override");
+                    changed |= ui.checkbox(&mut obj.hours_override, "").changed();
+                    ui.end_row();
+                }
+            });
+        if changed {
+            self.mark_dirty();
+        }
+        if object_type == ObjectType::Controller {
+            ui.separator();
+            self.controller_model_combos(ui, id);
+            self.controller_license_badge(ui, id);
+            self.controller_template_picker(ui, id);
+        }
+        if object_type == ObjectType::Equipment {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Point Template");
+                self.point_template_combo(ui, id);
+            });
+            self.make_model_fields(ui, id);
+            self.template_reset_controls(ui, id);
+            self.resync_point_kinds_button(ui, id);
+            self.hours_override_hint(ui, id);
+            self.commissioning_multiplier_field(ui, id);
+            self.equipment_estimate_line(ui, id);
+        }
+        if matches!(object_type, ObjectType::Equipment | ObjectType::Point) {
+            ui.separator();
+            self.property_groups_editor(ui, id);
+        }
+        ui.separator();
+        self.object_notes_editor(ui, id);
+    }
+
+    /// Tool actions shared by every `feature_control_center_view` layout, so each new
+    /// export or import is added once.
+    fn control_center_extra_actions(&mut self, ui: &mut Ui) {
+        if ui.button("Preview Equipment Retag").clicked() {
+            self.open_retag_preview();
+        }
+        if ui.button("Import Points (CSV)").clicked() {
+            self.import_points_csv();
+        }
+        if ui.button("Export Assignment Matrix").clicked() {
+            self.export_assignment_matrix();
+        }
+        if ui.button("Export BACnet Points").clicked() {
+            self.export_bacnet_points();
+        }
+        if ui.button("Export Bill of Materials").clicked() {
+            self.export_bill_of_materials();
+        }
+        if ui.button("Validate Project").clicked() {
+            self.validation_report = Some(self.validate_project());
+        }
+        if ui.button("Export Overview Image").clicked() {
+            self.export_embedded_asset(EmbeddedAsset::OverviewImage);
+        }
+        if ui.button("Export Current Drawing PDF").clicked() {
+            self.export_embedded_asset(EmbeddedAsset::DrawingPdf);
+        }
+        if ui.button("Export Wiring Report").clicked() {
+            self.export_wiring_report();
+        }
+        if ui.button("Export Project (JSON)").clicked() {
+            self.export_project_json();
+        }
+        if ui.button("Import Project (JSON)").clicked() {
+            self.request_discard(ui.ctx(), DiscardAction::ImportProjectJson);
+        }
+        if ui.button("Save as Preset").clicked() {
+            self.save_project_preset();
+        }
+        if ui.button("New from Preset").clicked() {
+            self.request_discard(ui.ctx(), DiscardAction::NewFromPreset);
+        }
+    }
 
-        ui.add_space(10.0);
+    /// Drawing canvas with the placement and wire tools. Tokens and wires live in page
+    /// space; `overlay_to_screen` applies the current zoom and pan.
+    fn drawings_overlay_view(&mut self, ui: &mut Ui) {
         ui.horizontal_wrapped(|ui| {
-            if ui.button("Go to Project Settings").clicked() {
-                self.current_view = ToolView::ProjectSettings;
+            self.drawing_selector(ui);
+            self.overlay_page_nav(ui);
+            ui.separator();
+            for tool in [OverlayTool::PlaceController, OverlayTool::PlaceEquipment, OverlayTool::Route] {
+                if ui.selectable_value(&mut self.overlay_tool, tool, tool.label()).changed() {
+                    self.overlay_wire_start = None;
+                }
             }
-            if ui.button("Go to Hours Estimator").clicked() {
-                self.current_view = ToolView::HoursEstimator;
+            if self.overlay_tool == OverlayTool::Route {
+                ui.separator();
+                self.wire_snap_toggle(ui);
             }
-            if ui.button("Go to Drawings Overlay").clicked() {
-                self.current_view = ToolView::DrawingsOverlay;
+        });
+        let viewport = ui.available_size();
+        ui.horizontal_wrapped(|ui| {
+            self.overlay_search_bar(ui, viewport);
+            ui.separator();
+            self.overlay_status_legend(ui);
+        });
+        ui.horizontal(|ui| {
+            self.overlay_zoom_buttons(ui, viewport);
+            self.overlay_fit_buttons(ui, viewport);
+            if ui.button("Export Marked-up Drawing").clicked() {
+                self.export_marked_up_drawing();
             }
-            if ui.button("Go to Templates").clicked() {
-                self.current_view = ToolView::Templates;
+            ui.separator();
+            self.overlay_snap_toggle(ui);
+        });
+        self.overlay_bulk_status_bar(ui);
+        self.overlay_history_panel(ui);
+        self.overlay_calibration_bar(ui);
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let canvas_min = response.rect.min;
+        self.overlay_scroll_zoom(ui, &response, canvas_min);
+        painter.rect_filled(response.rect, 0.0, Color32::from_rgb(12, 16, 24));
+        if let Some(texture) = &self.overlay_texture {
+            let page = egui::Rect::from_min_size(
+                canvas_min + self.overlay_pan,
+                self.overlay_page_size() * self.overlay_zoom,
+            );
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(texture.id(), page, uv, Color32::WHITE);
+        }
+        self.paint_overlay_grid(&painter, response.rect);
+        if response.dragged_by(egui::PointerButton::Middle) {
+            self.overlay_pan += response.drag_delta();
+        }
+        if !self.overlay_calibration_input(&response, canvas_min)
+            && !self.overlay_token_drag(&response, canvas_min)
+        {
+            self.overlay_canvas_click(&response, canvas_min);
+        }
+        self.overlay_rubber_band(&response, &painter, canvas_min);
+        self.overlay_context_menu(&response, canvas_min);
+        self.paint_overlay_items(&painter, canvas_min);
+        self.paint_overlay_selection(&painter, canvas_min);
+        self.overlay_selection_keys(ui.ctx());
+        self.overlay_token_tooltip(&response, canvas_min);
+        self.overlay_render_indicator(ui, response.rect);
+    }
+
+    /// Primary click on the canvas: places the selected object's token, or sets the next
+    /// wire end, depending on `overlay_tool`.
+    fn overlay_canvas_click(&mut self, response: &egui::Response, canvas_min: egui::Pos2) {
+        if !response.clicked() {
+            return;
+        }
+        let Some(pos) = response.interact_pointer_pos() else {
+            return;
+        };
+        let kind = match self.overlay_tool {
+            OverlayTool::Route => {
+                let (end, to_object) = self.wire_endpoint(canvas_min, pos);
+                let Some((start, from_object)) = self.overlay_wire_start.take() else {
+                    self.overlay_wire_start = Some((end, to_object));
+                    return;
+                };
+                self.push_overlay_history_as("Added wire");
+                self.project.overlay_lines.push(OverlayLine {
+                    from: start,
+                    to: end,
+                    from_object,
+                    to_object,
+                    page: self.overlay_page,
+                    ..Default::default()
+                });
+                self.mark_dirty();
+                return;
             }
+            OverlayTool::PlaceController => ObjectType::Controller,
+            OverlayTool::PlaceEquipment => ObjectType::Equipment,
+        };
+        let Some(object_id) = self
+            .selected_object
+            .filter(|id| self.project.objects.iter().any(|o| o.id == *id && o.object_type == kind))
+        else {
+            self.status = format!("Select a {kind:?} in the tree to place it");
+            return;
+        };
+        let [x, y] = self.overlay_placement_point(canvas_min, pos);
+        self.push_overlay_history_as("Placed token");
+        self.project.overlay_nodes.push(OverlayNode {
+            object_id,
+            x,
+            y,
+            page: self.overlay_page,
+            ..Default::default()
         });
+        self.mark_dirty();
+    }
 
-        ui.add_space(8.0);
-        ui.horizontal_wrapped(|ui| {
-            if ui.button("Run Health Check").clicked() {
-                self.status = match self.project_health_summary() {
-                    Ok(summary) => summary,
-                    Err(err) => format!("Health check failed: {err:#}"),
+    /// Wires, then tokens with their labels, for the current page.
+    fn paint_overlay_items(&self, painter: &egui::Painter, canvas_min: egui::Pos2) {
+        let to_screen =
+            |x: f32, y: f32| overlay_to_screen(canvas_min, self.overlay_pan, self.overlay_zoom, x, y);
+        let wire = egui::Stroke::new(2.0, self.accent());
+        for line in self.project.overlay_lines.iter().filter(|l| l.page == self.overlay_page) {
+            painter.line_segment([to_screen(line.from[0], line.from[1]), to_screen(line.to[0], line.to[1])], wire);
+        }
+        if let Some(([x, y], _)) = self.overlay_wire_start {
+            painter.circle_stroke(to_screen(x, y), 4.0, wire);
+        }
+        let calibration: Vec<egui::Pos2> = self
+            .calibration_points
+            .iter()
+            .map(|&[x, y]| to_screen(x, y))
+            .collect();
+        let ruler = egui::Stroke::new(2.0, Color32::from_rgb(250, 210, 80));
+        if let [a, b] = calibration[..] {
+            painter.line_segment([a, b], ruler);
+        }
+        for point in calibration {
+            painter.circle_stroke(point, 5.0, ruler);
+        }
+        let radius = OVERLAY_HIT_RADIUS * 0.75;
+        for (idx, node) in self.project.overlay_nodes.iter().enumerate() {
+            if node.page != self.overlay_page {
+                continue;
+            }
+            let center = to_screen(node.x, node.y);
+            painter.circle_filled(center, radius, node.status.color());
+            if let Some((glyph, color)) = self.overlay_node_glyph(node) {
+                painter.circle_stroke(center, radius, egui::Stroke::new(2.0, color));
+                painter.text(
+                    center,
+                    egui::Align2::CENTER_CENTER,
+                    glyph,
+                    egui::FontId::proportional(radius),
+                    Color32::WHITE,
+                );
+            }
+            if self.overlay_highlight == Some(idx) {
+                painter.circle_stroke(center, radius + 4.0, egui::Stroke::new(2.0, Color32::WHITE));
+            }
+            painter.text(
+                center + egui::vec2(radius + 4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                self.overlay_node_label(node),
+                egui::FontId::proportional(12.0),
+                Color32::from_rgb(226, 233, 242),
+            );
+        }
+    }
+
+    /// Replaces the open project with an empty one. Reached through `request_discard`
+    /// so unsaved work is never dropped silently.
+    fn new_project(&mut self) {
+        self.project = Project::default();
+        self.project_path = None;
+        self.selected_object = None;
+        self.selected_objects.clear();
+        self.overview_image_bytes = None;
+        self.overlay_pdf_bytes = None;
+        // `drawing_assets` bundles these directly; stale bytes would ride into the next save.
+        self.drawing_pdf_bytes.clear();
+        self.overlay_page = 0;
+        self.overlay_page_count = 0;
+        self.calibration_points.clear();
+        self.overlay_texture = None;
+        self.project_undo.clear();
+        self.project_redo.clear();
+        self.overlay_undo_stack.clear();
+        self.overlay_redo_stack.clear();
+        self.overlay_selection.clear();
+        self.status = "New project".to_string();
+    }
+
+    /// Saves to `project_path`, asking for a location the first time. A picked path
+    /// without `.m8` gets it appended, as in `save_project_as`.
+    fn save_project(&mut self) {
+        let (path, corrected) = match self.project_path.clone() {
+            Some(path) => (path, false),
+            None => {
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter("AutoMate Project", &["m8"])
+                    .set_file_name(format!("{}.m8", sanitize_file_stem(&self.project.name)))
+                    .save_file()
+                else {
+                    return;
                 };
+                with_m8_extension(path)
             }
-            if ui.button("Validate Export Readiness").clicked() {
-                self.status = match self.validate_export_readiness() {
-                    Ok(_) => "Export package ready".to_string(),
-                    Err(err) => err,
+        };
+        match self.save_project_to_path(&path) {
+            Ok(()) => {
+                self.status = if corrected {
+                    format!("Saved {} (added .m8 so it shows in Load)", path.display())
+                } else {
+                    format!("Saved {}", path.display())
                 };
+                self.project_path = Some(path);
             }
-            if ui.button("Export Proposal Markdown").clicked() {
-                self.export_proposal_markdown();
+            Err(err) => self.status = format!("Save failed: {err}"),
+        }
+    }
+
+    /// Writes the project as a `.m8` bundle and marks it clean.
+    fn save_project_to_path(&mut self, path: &std::path::Path) -> Result<(), AppIoError> {
+        self.stash_active_drawing();
+        let payload = serde_json::to_vec_pretty(&self.project)?;
+        let mut assets = self.drawing_assets();
+        if let Some(bytes) = &self.overview_image_bytes {
+            assets.push(("assets/overview.png".to_string(), bytes.clone()));
+        }
+        write_m8_bundle(path, &payload, &assets)?;
+        tracing::info!(path = %path.display(), "saved project");
+        self.forget_autosaves_after_save(path);
+        self.mark_clean();
+        Ok(())
+    }
+
+    /// A real save supersedes every autosave of this project: delete them and remember
+    /// where the project lives so the next startup can compare against it.
+    fn forget_autosaves_after_save(&mut self, path: &std::path::Path) {
+        let project_uuid = self.project.project_uuid;
+        if let Err(err) = clear_project_autosaves(project_uuid) {
+            tracing::warn!(%err, "could not clear autosaves");
+        }
+        self.recovery_candidates
+            .retain(|candidate| candidate.project_uuid != Some(project_uuid));
+        self.preferences
+            .saved_project_paths
+            .insert(project_uuid, path.to_path_buf());
+        if let Err(err) = self.preferences.save() {
+            tracing::warn!(%err, "could not record the saved project path");
+        }
+    }
+
+    fn load_project(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("AutoMate Project", &["m8"])
+            .pick_file()
+        else {
+            return;
+        };
+        match self.load_project_from_path(&path) {
+            Ok(()) => {
+                self.status = format!("Loaded {}", self.project.name);
+                self.project_path = Some(path);
             }
-            if ui.button("Export Objects CSV").clicked() {
-                self.export_objects_csv();
+            Err(err) => self.status = format!("Load failed: {err}"),
+        }
+    }
+
+    /// Opens a `.m8` bundle in place of the current project and marks it clean.
+    fn load_project_from_path(&mut self, path: &std::path::Path) -> Result<(), AppIoError> {
+        let mut entries = read_m8_bundle(path)?;
+        let payload = entries.remove("project.json").ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "bundle has no project.json")
+        })?;
+        let mut project: Project = serde_json::from_slice(&payload)?;
+        migrate_project(&mut project);
+        self.project = project;
+        self.normalize_loaded_project();
+        self.overview_image_bytes = entries.remove("assets/overview.png");
+        // Pre-drawing-set bundles stored their single PDF as `assets/{overlay_pdf}`.
+        self.overlay_pdf_bytes = self
+            .project
+            .overlay_pdf
+            .as_ref()
+            .filter(|_| self.project.drawings.is_empty())
+            .and_then(|name| entries.remove(&format!("assets/{name}")));
+        self.overlay_page = 0;
+        self.restore_drawings(|name| entries.remove(name));
+        self.selected_object = None;
+        self.selected_objects.clear();
+        self.project_undo.clear();
+        self.project_redo.clear();
+        self.overlay_undo_stack.clear();
+        self.overlay_redo_stack.clear();
+        self.overlay_selection.clear();
+        tracing::info!(path = %path.display(), "loaded project");
+        self.mark_clean();
+        Ok(())
+    }
+
+    /// Project menu and view switcher for the studio toolbar. New and Load go through
+    /// `request_discard` so unsaved changes get a prompt first.
+    fn toolbar_dropdowns(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.menu_button("Project", |ui| {
+                if ui.button("🆕 New").clicked() {
+                    self.request_discard(ui.ctx(), DiscardAction::NewProject);
+                    ui.close_menu();
+                }
+                if ui.button("📂 Load…").clicked() {
+                    self.request_discard(ui.ctx(), DiscardAction::LoadProject);
+                    ui.close_menu();
+                }
+                if ui.button("💾 Save").clicked() {
+                    self.save_project();
+                    ui.close_menu();
+                }
+                self.project_menu_extras(ui);
+            });
+            ui.separator();
+            for view in ToolView::iter() {
+                ui.selectable_value(&mut self.current_view, view, view.label());
             }
-            if ui.button("Export Project Schema").clicked() {
-                self.export_project_schema();
+            ui.separator();
+            if ui.button("⚙ Settings").clicked() {
+                self.show_preferences = true;
             }
+            ui.menu_button("Help", |ui| self.help_menu_extras(ui));
         });
     }
 
-    fn feature_control_center_view(&mut self, ui: &mut Ui) {
-        ui.heading("Feature Control Center");
-        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
-        ui.add_space(8.0);
+    /// Workstation preferences window opened from the toolbar.
+    fn preferences_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_preferences;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_source("preferences_scroll")
+                    .show(ui, |ui| self.preferences_editor(ui));
+            });
+        self.show_preferences = open;
+    }
 
-        let (ready_count, ready_total) = self.export_readiness_score();
-        let overlay_ready = self.project.overlay_pdf.is_some();
-        let template_ready = !self.user_templates.is_empty();
-        let has_equipment = self
-            .project
-            .objects
-            .iter()
-            .any(|o| o.object_type == ObjectType::Equipment);
+    /// Studio shortcuts: Cmd+S saves, Cmd+N and Cmd+O ask before replacing a dirty
+    /// project. Cmd+Z / Cmd+Shift+Z step the overlay history on the drawings view.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.app_screen != AppScreen::Studio {
+            return;
+        }
+        let command = |key| egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, key);
+        if ctx.input_mut(|i| i.consume_shortcut(&command(egui::Key::S))) {
+            self.save_project();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&command(egui::Key::N))) {
+            self.request_discard(ctx, DiscardAction::NewProject);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&command(egui::Key::O))) {
+            self.request_discard(ctx, DiscardAction::LoadProject);
+        }
+        if self.current_view != ToolView::DrawingsOverlay {
+            return;
+        }
+        let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z);
+        if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+            self.overlay_redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&command(egui::Key::Z))) {
+            self.overlay_undo();
+        }
+    }
+
+    /// Template Tool: one editable card per equipment template. Edits are written to the
+    /// user template store as they happen.
+    fn templates_view(&mut self, ui: &mut Ui) {
+        ui.heading("Equipment Templates");
+        self.template_library_actions(ui);
+        self.template_search_bar(ui);
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .id_source("templates_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                self.controller_templates_section(ui);
+                ui.add_space(8.0);
+                let indices = self.visible_template_indices();
+                self.template_category_sections(ui, &indices, |app, ui, idx| app.template_card(ui, idx));
+            });
+    }
 
+    fn template_card(&mut self, ui: &mut Ui, idx: usize) {
+        let Some(name) = self.user_templates.get(idx).map(|t| t.name.clone()) else {
+            return;
+        };
+        let mut changed = false;
         Self::card_frame().show(ui, |ui| {
-            ui.label(RichText::new("Core Capabilities").strong());
-            ui.separator();
-            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
-            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
-            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
+            ui.horizontal(|ui| {
+                self.template_export_checkbox(ui, &name);
+                let template = &mut self.user_templates[idx];
+                ui.strong(&template.name);
+                ui.label(RichText::new(&template.equipment_type).weak());
+                changed |= Self::template_archive_toggle(ui, template);
+                self.template_duplicate_button(ui, idx);
+            });
+            let template = &mut self.user_templates[idx];
+            changed |= Self::template_category_editor(ui, template);
+            changed |= Self::template_driver_editor(ui, template);
+            egui::CollapsingHeader::new(format!("Points ({})", template.points.len()))
+                .id_source(("template_points", idx))
+                .show(ui, |ui| {
+                    for (p_idx, point) in template.points.iter_mut().enumerate() {
+                        ui.push_id(p_idx, |ui| {
+                            ui.label(format!("{} {}", point.kind.icon(), point.name));
+                            changed |= Self::property_groups_fields(
+                                ui,
+                                "template_point_groups",
+                                "Default properties",
+                                &mut point.property_groups,
+                            );
+                        });
+                    }
+                });
+            self.template_apply_all_button(ui, &name);
         });
+        if changed {
+            self.save_user_templates();
+        }
+    }
 
-        ui.add_space(10.0);
-        ui.horizontal_wrapped(|ui| {
-            if ui.button("Go to Project Settings").clicked() {
-                self.current_view = ToolView::ProjectSettings;
-            }
-            if ui.button("Go to Hours Estimator").clicked() {
-                self.current_view = ToolView::HoursEstimator;
-            }
-            if ui.button("Go to Drawings Overlay").clicked() {
-                self.current_view = ToolView::DrawingsOverlay;
+    /// Builds one row per equipment, and one per controller with points wired straight
+    /// to it, then sums the rows into the project totals (engineering, graphics,
+    /// commissioning, custom, overhead, total). Each point counts at its
+    /// `point_kind_weights` weight, including under a `PointsBased` override's own
+    /// per-point rate; equipment add their template's graphics and commissioning
+    /// drivers, with commissioning scaled by the equipment's own commissioning
+    /// multiplier. Row hours are scaled by the complexity, renovation and integration
+    /// factors. Custom hour lines are added to their category's total, leaving only
+    /// "Other" lines in the custom slot; QA/PM/risk overhead is a percentage of
+    /// everything else.
+    fn estimate_hours(&self) -> HoursEstimate {
+        let estimator = &self.project.estimator;
+        let factors = estimator.complexity_factor * estimator.renovation_factor * estimator.integration_factor;
+        let weights = &estimator.point_kind_weights;
+        let mut rows = Vec::new();
+        let mut kind_hours = [0.0_f32; 5];
+        let mut add_kind_hours = |points: &[&BasObject], per_point: f32| {
+            for point in points.iter().filter(|o| o.object_type == ObjectType::Point) {
+                if let Some(idx) = POINT_KINDS.iter().position(|&k| k == point.point_kind) {
+                    kind_hours[idx] += weights.weight(point.point_kind) * per_point * factors;
+                }
             }
-            if ui.button("Go to Templates").clicked() {
-                self.current_view = ToolView::Templates;
+        };
+        for obj in &self.project.objects {
+            let points: Vec<&BasObject> =
+                self.project.objects.iter().filter(|o| o.parent_id == Some(obj.id)).collect();
+            let base = match obj.object_type {
+                ObjectType::Equipment => {
+                    // A `PointsBased` hours override swaps in its own per-point rate, but
+                    // each point still counts at its kind weight.
+                    let per_point =
+                        if obj.hours_override && obj.hours_override_mode == HoursOverrideMode::PointsBased {
+                            obj.override_hours_per_point
+                        } else {
+                            estimator.hours_per_point
+                        };
+                    let engineering = weights.weighted_count(points.iter().copied()) * per_point;
+                    add_kind_hours(&points, per_point);
+                    let template = self.user_templates.iter().find(|t| t.name == obj.template_name);
+                    let graphics = template.map_or(0.0, |t| template_graphics_hours(t, estimator));
+                    // Like the other overrides, the commissioning multiplier only counts
+                    // while `hours_override` is on.
+                    let cx_multiplier = if obj.hours_override {
+                        obj.commissioning_multiplier.max(0.0)
+                    } else {
+                        1.0
+                    };
+                    let commissioning =
+                        template.map_or(0.0, |t| template_commissioning_hours(t, estimator)) * cx_multiplier;
+                    [engineering, graphics, commissioning]
+                }
+                // Points wired straight to a controller, with no equipment in between.
+                ObjectType::Controller => {
+                    let weighted = weights.weighted_count(points.iter().copied());
+                    if weighted == 0.0 {
+                        continue;
+                    }
+                    add_kind_hours(&points, estimator.hours_per_point);
+                    [weighted * estimator.hours_per_point, 0.0, 0.0]
+                }
+                _ => continue,
+            };
+            rows.push(EstimateRow {
+                object_id: obj.id,
+                building_id: self.ancestor_of_type(obj.id, ObjectType::Building).map(|b| b.id),
+                controller_id: self.ancestor_of_type(obj.id, ObjectType::Controller).map(|c| c.id),
+                hours: base.map(|hours| hours * factors),
+            });
+        }
+        let mut totals = [0.0_f32; 6];
+        for row in &rows {
+            for (sum, value) in totals.iter_mut().zip(row.hours) {
+                *sum += value;
             }
-        });
+        }
+        // Custom lines land in their own category; only "Other" stays in the custom slot.
+        let [c_eng, c_gfx, c_cx, c_other] = self.custom_hours_by_category();
+        totals[0] += c_eng;
+        totals[1] += c_gfx;
+        totals[2] += c_cx;
+        totals[3] = c_other;
+        let overhead_percent = estimator.qa_percent + estimator.pm_percent + estimator.risk_percent;
+        let subtotal: f32 = totals[..4].iter().sum();
+        totals[4] = subtotal * overhead_percent / 100.0;
+        totals[5] = subtotal + totals[4];
+        HoursEstimate {
+            rows,
+            totals,
+            kind_hours,
+        }
+    }
 
+    /// Hours Estimator: category totals and the Hours Adjustments popup.
+    fn hours_estimator_view(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Hours Estimate");
+            ui.menu_button("⚙ Hours Adjustments", |ui| {
+                ui.set_min_width(340.0);
+                // One estimate per frame feeds every slider's readout.
+                let estimate = self.estimate_hours();
+                self.estimator_factor_sliders(ui, &estimate);
+                ui.separator();
+                self.point_kind_weight_sliders(ui, &estimate);
+            });
+            self.copy_estimate_summary_button(ui);
+        });
+        ui.add_space(6.0);
+        let [eng, gfx, cx, custom, overhead, total] = self.estimate_hours().totals;
+        Self::card_frame().show(ui, |ui| {
+            egui::Grid::new("hours_summary")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (label, hours) in [
+                        ("Engineering", eng),
+                        ("Graphics", gfx),
+                        ("Commissioning", cx),
+                        ("Other custom", custom),
+                        ("QA / PM / Risk", overhead),
+                    ] {
+                        ui.label(label);
+                        ui.monospace(format!("{hours:.1} h"));
+                        ui.end_row();
+                    }
+                    self.custom_contribution_rows(ui);
+                    ui.strong("Total");
+                    ui.strong(format!("{total:.1} h"));
+                    ui.end_row();
+                });
+        });
         ui.add_space(8.0);
-        ui.horizontal_wrapped(|ui| {
-            if ui.button("Run Health Check").clicked() {
-                self.status = match self.project_health_summary() {
-                    Ok(summary) => summary,
-                    Err(err) => format!("Health check failed: {err:#}"),
-                };
-            }
-            if ui.button("Validate Export Readiness").clicked() {
-                self.status = match self.validate_export_readiness() {
-                    Ok(_) => "Export package ready".to_string(),
-                    Err(err) => err,
-                };
-            }
-            if ui.button("Export Proposal Markdown").clicked() {
-                self.export_proposal_markdown();
-            }
-            if ui.button("Export Objects CSV").clicked() {
-                self.export_objects_csv();
-            }
-            if ui.button("Export Project Schema").clicked() {
-                self.export_project_schema();
-            }
+        self.hours_breakdown_card(ui);
+        ui.add_space(8.0);
+        self.estimate_snapshots_card(ui);
+        ui.add_space(8.0);
+        self.expenses_card(ui);
+        ui.add_space(8.0);
+        self.labor_cost_card(ui);
+    }
+
+    /// Object tree panel: search, the multi-selection toolbar, and the tree itself.
+    fn left_sidebar(&mut self, ui: &mut Ui) {
+        ui.heading("Objects");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.object_search_query)
+                    .hint_text("🔍 Search")
+                    .desired_width(ui.available_width() - 56.0),
+            );
+            self.tree_collapse_controls(ui);
         });
+        self.tree_type_filter_bar(ui);
+        self.tree_search_match_count(ui);
+        self.sync_search_expansion();
+        if ui.button("➕ Add Building").clicked() {
+            self.add_object(None, ObjectType::Building);
+        }
+        self.tree_selection_toolbar(ui);
+        ui.separator();
+        let ancestors = self.search_match_ancestors();
+        let tree = egui::ScrollArea::vertical()
+            .id_source("object_tree")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for id in self.filtered_root_ids() {
+                    self.object_node(ui, id, &ancestors);
+                }
+            });
+        self.update_tree_focus(ui, tree.inner_rect);
+        self.tree_keyboard_navigation(ui);
+        self.handle_subtree_paste(ui);
     }
 
-    fn feature_control_center_view(&mut self, ui: &mut Ui) {
-        ui.heading("Feature Control Center");
-        ui.label("Everything critical in AutoMate is still here. Use this panel as the one-stop operational checklist.");
-        ui.add_space(8.0);
+    /// Top-level objects for the tree. While a search or type filter is active only roots
+    /// that match, or lead to a match, are kept.
+    fn filtered_root_ids(&self) -> Vec<u64> {
+        let ancestors = self.search_match_ancestors();
+        self.project
+            .objects
+            .iter()
+            .filter(|o| o.parent_id.is_none() && self.tree_row_visible(o, &ancestors))
+            .map(|o| o.id)
+            .collect()
+    }
 
-        let (ready_count, ready_total) = self.export_readiness_score();
-        let overlay_ready = self.project.overlay_pdf.is_some();
-        let template_ready = !self.user_templates.is_empty();
-        let has_equipment = self
+    /// One tree row and, unless collapsed, its children. Clicks go through
+    /// `handle_tree_click` so Ctrl/Shift build up `selected_objects`.
+    fn object_node(&mut self, ui: &mut Ui, id: u64, ancestors: &HashSet<u64>) {
+        let Some(obj) = self.project.objects.iter().find(|o| o.id == id) else {
+            return;
+        };
+        let children: Vec<u64> = self
             .project
             .objects
             .iter()
-            .any(|o| o.object_type == ObjectType::Equipment);
-
-        Self::card_frame().show(ui, |ui| {
-            ui.label(RichText::new("Core Capabilities").strong());
-            ui.separator();
-            ui.label(format!("{} Project settings + proposal inputs", if !self.project.name.trim().is_empty() { "✅" } else { "⚠" }));
-            ui.label(format!("{} Hours estimator", if has_equipment { "✅" } else { "⚠" }));
-            ui.label(format!("{} Drawings overlay", if overlay_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Template engine", if template_ready { "✅" } else { "⚠" }));
-            ui.label(format!("{} Export readiness ({ready_count}/{ready_total})", if ready_count == ready_total { "✅" } else { "⚠" }));
-        });
-
-        ui.add_space(10.0);
-        ui.horizontal_wrapped(|ui| {
-            if ui.button("Go to Project Settings").clicked() {
-                self.current_view = ToolView::ProjectSettings;
+            .filter(|o| o.parent_id == Some(id) && self.tree_row_visible(o, ancestors))
+            .map(|o| o.id)
+            .collect();
+        let (icon, icon_color) = Self::object_style(obj);
+        let markers = Self::object_row_markers(obj);
+        let row_matches = self.tree_filter_active() && self.tree_row_matches(obj);
+        let name = obj.name.clone();
+        let collapsed = self.collapsed_tree_nodes.contains(&id);
+        let selected = self.selected_objects.contains(&id) || self.selected_object == Some(id);
+        ui.horizontal(|ui| {
+            if children.is_empty() {
+                ui.add_space(18.0);
+            } else if ui.small_button(if collapsed { "▸" } else { "▾" }).clicked() {
+                if collapsed {
+                    self.collapsed_tree_nodes.remove(&id);
+                } else {
+                    self.collapsed_tree_nodes.insert(id);
+                }
             }
-            if ui.button("Go to Hours Estimator").clicked() {
-                self.current_view = ToolView::HoursEstimator;
+            let icon = RichText::new(icon);
+            ui.label(match icon_color {
+                Some(color) => icon.color(color),
+                None => icon,
+            });
+            let response = ui
+                .selectable_label(selected, self.highlighted_object_name(ui, &name))
+                .interact(egui::Sense::drag());
+            response.dnd_set_drag_payload(id);
+            if self.tree_scroll_to_match && row_matches {
+                // First filtered hit after a status-bar click: bring the tree to it.
+                response.scroll_to_me(Some(egui::Align::TOP));
+                self.tree_scroll_to_match = false;
             }
-            if ui.button("Go to Drawings Overlay").clicked() {
-                self.current_view = ToolView::DrawingsOverlay;
+            for (marker, hint) in markers {
+                ui.label(RichText::new(marker).small().weak()).on_hover_text(hint);
             }
-            if ui.button("Go to Templates").clicked() {
-                self.current_view = ToolView::Templates;
+            if response.clicked() {
+                self.handle_tree_click(id, ui.input(|i| i.modifiers));
+            }
+            self.tree_drop_target(&response, id);
+            response.context_menu(|ui| self.object_context_menu(ui, id));
+        });
+        if collapsed {
+            return;
+        }
+        ui.indent(("tree_children", id), |ui| {
+            for child in children {
+                self.object_node(ui, child, ancestors);
             }
         });
+    }
 
-        ui.add_space(8.0);
-        ui.horizontal_wrapped(|ui| {
-            if ui.button("Run Health Check").clicked() {
-                self.status = match self.project_health_summary() {
-                    Ok(summary) => summary,
-                    Err(err) => format!("Health check failed: {err:#}"),
-                };
+    /// Right-click menu for a tree row.
+    fn object_context_menu(&mut self, ui: &mut Ui, id: u64) {
+        let Some(object_type) = self.project.objects.iter().find(|o| o.id == id).map(|o| o.object_type) else {
+            return;
+        };
+        if let Some(child_type) = Self::child_object_type(object_type) {
+            if ui.button(format!("➕ Add {child_type:?}")).clicked() {
+                self.add_object(Some(id), child_type);
+                ui.close_menu();
             }
-            if ui.button("Validate Export Readiness").clicked() {
-                self.status = match self.validate_export_readiness() {
-                    Ok(_) => "Export package ready".to_string(),
-                    Err(err) => err,
-                };
+        }
+        if object_type == ObjectType::Controller {
+            self.bulk_equipment_menu_item(ui, id);
+            self.place_controller_equipment_menu_item(ui, id);
+        }
+        self.reorder_menu_items(ui, id);
+        ui.separator();
+        self.subtree_clipboard_menu_items(ui, id);
+        ui.separator();
+        if ui.button("🗑 Delete").clicked() {
+            self.push_project_history();
+            self.remove_object_subtree(id);
+            self.selected_objects.remove(&id);
+            if self.selected_object == Some(id) {
+                self.selected_object = None;
             }
-            if ui.button("Export Proposal Markdown").clicked() {
-                self.export_proposal_markdown();
+            self.mark_dirty();
+            ui.close_menu();
+        }
+    }
+
+    /// Removes `id` and all of its descendants, along with their tokens on every drawing
+    /// sheet. Wires attached to a removed token stay where they are but stop following it.
+    fn remove_object_subtree(&mut self, id: u64) {
+        let mut removed = HashSet::from([id]);
+        loop {
+            let before = removed.len();
+            for obj in &self.project.objects {
+                if obj.parent_id.is_some_and(|p| removed.contains(&p)) {
+                    removed.insert(obj.id);
+                }
+            }
+            if removed.len() == before {
+                break;
             }
-            if ui.button("Export Objects CSV").clicked() {
-                self.export_objects_csv();
+        }
+        self.project.objects.retain(|o| !removed.contains(&o.id));
+        let detach = |nodes: &mut Vec<OverlayNode>, lines: &mut Vec<OverlayLine>| {
+            nodes.retain(|n| !removed.contains(&n.object_id));
+            for line in lines {
+                if line.from_object.is_some_and(|o| removed.contains(&o)) {
+                    line.from_object = None;
+                }
+                if line.to_object.is_some_and(|o| removed.contains(&o)) {
+                    line.to_object = None;
+                }
             }
-            if ui.button("Export Project Schema").clicked() {
-                self.export_project_schema();
+        };
+        detach(&mut self.project.overlay_nodes, &mut self.project.overlay_lines);
+        for sheet in &mut self.project.drawings {
+            detach(&mut sheet.overlay_nodes, &mut sheet.overlay_lines);
+        }
+        self.selected_objects.retain(|o| !removed.contains(o));
+        self.overlay_selection.clear();
+    }
+
+    /// Adds a default-named object under `parent`, opens the parent, and selects it.
+    fn add_object(&mut self, parent: Option<u64>, object_type: ObjectType) -> u64 {
+        self.push_project_history();
+        let id = self.project.next_id;
+        self.project.next_id += 1;
+        self.project.objects.push(BasObject {
+            id,
+            name: format!("New {object_type:?}"),
+            object_type,
+            parent_id: parent,
+            ..Default::default()
+        });
+        if let Some(parent) = parent {
+            self.collapsed_tree_nodes.remove(&parent);
+        }
+        self.handle_tree_click(id, egui::Modifiers::NONE);
+        self.mark_dirty();
+        id
+    }
+
+    /// Everything worth fixing before the project goes out, shown under `workspace_header`.
+    fn ux_health_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+        issues.extend(self.license_capacity_issues());
+        issues.extend(self.proposal_date_issues());
+        issues.extend(self.untemplated_equipment_issues());
+        issues.extend(self.controller_loading_issues());
+        issues
+    }
+
+    /// Project name and active view above the central panel, plus any health warnings.
+    fn workspace_header(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            self.brand_mark(ui, 24.0);
+            ui.heading(self.project.name.as_str());
+            ui.label(RichText::new(self.current_view.label()).weak());
+        });
+        self.point_mix_header(ui);
+        let issues = self.ux_health_issues();
+        if issues.is_empty() {
+            return;
+        }
+        egui::CollapsingHeader::new(
+            RichText::new(format!("⚠ {} issue(s)", issues.len())).color(Color32::from_rgb(230, 170, 60)),
+        )
+        .id_source("ux_health_issues")
+        .show(ui, |ui| {
+            for issue in issues {
+                ui.label(issue);
             }
+            self.untemplated_equipment_fix(ui);
         });
     }
+
+    /// Brings equipment in line with its template: the equipment type unless
+    /// overridden, and one child point per template point. Points are matched by name
+    /// and existing ones keep their kind; new points, and existing points with no
+    /// property groups yet, get the template point's default groups.
+    fn sync_equipment_from_template(&mut self, equipment_id: u64) {
+        let Some(template) = self
+            .project
+            .objects
+            .iter()
+            .find(|o| o.id == equipment_id)
+            .and_then(|e| self.user_templates.iter().find(|t| t.name == e.template_name))
+            .cloned()
+        else {
+            return;
+        };
+        if let Some(equipment) = self.project.objects.iter_mut().find(|o| o.id == equipment_id) {
+            if !equipment.equipment_type_override {
+                equipment.equipment_type = template.equipment_type.clone();
+            }
+        }
+        for point in &template.points {
+            let existing = self.project.objects.iter_mut().find(|o| {
+                o.parent_id == Some(equipment_id) && o.object_type == ObjectType::Point && o.name == point.name
+            });
+            match existing {
+                Some(obj) => {
+                    if obj.property_groups.is_empty() {
+                        obj.property_groups = point.property_groups.clone();
+                    }
+                }
+                None => {
+                    let id = self.project.next_id;
+                    self.project.next_id += 1;
+                    self.project.objects.push(BasObject {
+                        id,
+                        name: point.name.clone(),
+                        object_type: ObjectType::Point,
+                        parent_id: Some(equipment_id),
+                        point_kind: point.kind,
+                        property_groups: point.property_groups.clone(),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    /// Operator sign-in card. Authenticate checks the passphrase against the stored
+    /// hash, or on first run sets one up for this workstation.
+    fn login_screen(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default()
+            .frame(Self::surface_panel().inner_margin(egui::Margin::same(24.0)))
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    self.brand_mark(ui, 48.0);
+                    ui.heading("AutoMate");
+                    ui.label(RichText::new("Sign in to continue").weak());
+                    ui.add_space(12.0);
+                    ui.add(egui::TextEdit::singleline(&mut self.login_username).hint_text("Operator ID"));
+                    let passphrase = ui.add(
+                        egui::TextEdit::singleline(&mut self.login_password)
+                            .password(true)
+                            .hint_text("Passphrase"),
+                    );
+                    self.first_run_password_fields(ui);
+                    if let Some(err) = &self.login_error {
+                        ui.colored_label(Color32::from_rgb(230, 80, 80), err);
+                    }
+                    ui.add_space(8.0);
+                    let submitted = passphrase.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Authenticate").clicked() || submitted {
+                        self.authenticate();
+                    }
+                });
+            });
+    }
+
+    /// Project name and the proposal inputs.
+    fn project_settings_view(&mut self, ui: &mut Ui) {
+        ui.heading("Project Settings");
+        ui.add_space(6.0);
+        egui::ScrollArea::vertical()
+            .id_source("project_settings_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                Self::card_frame().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Project name");
+                        if ui.text_edit_singleline(&mut self.project.name).changed() {
+                            self.mark_dirty();
+                        }
+                    });
+                    ui.separator();
+                    self.proposal_date_fields(ui);
+                    ui.separator();
+                    self.proposal_clause_fields(ui);
+                    self.proposal_export_mode_picker(ui);
+                    ui.separator();
+                    self.company_logo_settings(ui);
+                });
+            });
+    }
 }
 
 impl App for AutoMateApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+        self.poll_startup_preload();
+        self.poll_autosave();
+        self.poll_overlay_render(ctx);
         self.configure_viewport_for_screen(ctx);
+        // Consumed ahead of `handle_shortcuts` so Cmd+Shift+S never falls through to Save.
+        self.handle_save_as_shortcut(ctx);
+        self.handle_project_history_shortcuts(ctx);
+        self.handle_add_child_shortcut(ctx);
         self.handle_shortcuts(ctx);
+        self.intercept_close_request(ctx);
         if self.app_screen == AppScreen::Studio {
             self.draw_studio_background(ctx);
         }
-        ctx.set_pixels_per_point(self.project.settings.ui_scale);
+        ctx.set_pixels_per_point(self.preferences.ui_scale);
 
         let mut style = (*ctx.style()).clone();
         style.spacing.item_spacing = egui::vec2(6.0, 6.0);
@@ -555,8 +8065,11 @@ impl App for AutoMateApp {
             AppScreen::Splash => self.splash_screen(ctx),
             AppScreen::Login => self.login_screen(ctx),
             AppScreen::Studio => {
-                self.ensure_template_seeded();
-                self.autosave_project();
+                // Seeding before the stored library arrives would shadow it.
+                if self.startup_preload_rx.is_none() {
+                    self.ensure_template_seeded();
+                }
+                self.autosave_project_in_background();
                 self.titlebar(ctx, _frame);
                 egui::TopBottomPanel::top("toolbar")
                     .frame(Self::surface_panel())
@@ -568,8 +8081,10 @@ impl App for AutoMateApp {
                         ui.horizontal_wrapped(|ui| {
                             ui.label(self.status.as_str());
                             for (kind, count) in self.object_counts() {
-                                ui.label(format!("{} {}", kind.icon(), count));
+                                self.status_count_filter(ui, kind, count);
                             }
+                            ui.separator();
+                            self.save_state_indicator(ui);
                         });
                     });
 
@@ -582,8 +8097,16 @@ impl App for AutoMateApp {
                 egui::SidePanel::right("properties")
                     .resizable(true)
                     .default_width(360.0)
+                    .width_range(280.0..=720.0)
                     .frame(Self::surface_panel())
-                    .show(ctx, |ui| self.right_properties(ui));
+                    .show(ctx, |ui| {
+                        // Long override sections and point lists scroll instead of clipping,
+                        // which keeps Delete at the bottom reachable on small screens.
+                        egui::ScrollArea::vertical()
+                            .id_source("properties_scroll")
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| self.right_properties(ui));
+                    });
 
                 egui::CentralPanel::default()
                     .frame(Self::surface_panel().inner_margin(egui::Margin::same(18.0)))
@@ -601,8 +8124,896 @@ impl App for AutoMateApp {
                     });
 
                 self.dialogs(ctx);
+                self.template_reset_dialog(ctx);
+                self.unsaved_changes_dialog(ctx);
+                self.retag_preview_dialog(ctx);
+                self.recovery_dialog(ctx);
+                self.template_import_dialog(ctx);
+                self.bulk_equipment_dialog(ctx);
+                self.validation_dialog(ctx);
+                self.preferences_window(ctx);
             }
         }
         ctx.request_repaint();
     }
 }
+
+fn format_currency(value: f32) -> String {
+    format!("${value:.2}")
+}
+
+fn cost_summary_rows(summary: &CostSummary) -> [(&'static str, f32); 6] {
+    [
+        ("Labor", summary.labor),
+        ("Materials / licenses", summary.materials),
+        ("Subtotal", summary.subtotal()),
+        ("Markup", summary.markup),
+        ("Tax", summary.tax),
+        ("Total", summary.total()),
+    ]
+}
+
+fn cost_summary_markdown(summary: &CostSummary, terms: &CommercialTerms) -> String {
+    let mut out = String::from("## Cost Summary\n\n| Line | Amount |\n|---|---:|\n");
+    for (label, value) in cost_summary_rows(summary) {
+        let label = match label {
+            "Markup" => format!("Markup ({:.1}%)", terms.markup_percent),
+            "Tax" => format!("Tax ({:.2}%)", terms.tax_percent),
+            _ => label.to_string(),
+        };
+        out.push_str(&format!("| {label} | {} |\n", format_currency(value)));
+    }
+    out
+}
+
+fn sanitize_file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if stem.is_empty() {
+        "project".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Load dialogs filter to `.m8`, so saves are forced onto that extension.
+/// Returns the corrected path and whether it had to change.
+fn with_m8_extension(path: std::path::PathBuf) -> (std::path::PathBuf, bool) {
+    let is_m8 = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m8"));
+    if is_m8 {
+        return (path, false);
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".m8");
+    (path.with_file_name(name), true)
+}
+
+fn overlay_to_screen(canvas_min: egui::Pos2, pan: egui::Vec2, zoom: f32, x: f32, y: f32) -> egui::Pos2 {
+    canvas_min + pan + egui::vec2(x, y) * zoom
+}
+
+fn overlay_to_page(canvas_min: egui::Pos2, pan: egui::Vec2, zoom: f32, pos: egui::Pos2) -> [f32; 2] {
+    let page = (pos - canvas_min - pan) / zoom.max(f32::EPSILON);
+    [page.x, page.y]
+}
+
+fn hour_driver_combo(ui: &mut Ui, id: impl std::hash::Hash, driver: &mut HourDriver) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id)
+        .selected_text(driver.label())
+        .show_ui(ui, |ui| {
+            for option in HourDriver::iter() {
+                changed |= ui.selectable_value(driver, option, option.label()).changed();
+            }
+        });
+    changed
+}
+
+/// Graphics hours for one equipment built from `template`, honoring its driver.
+fn template_graphics_hours(template: &EquipmentTemplate, estimator: &EstimatorSettings) -> f32 {
+    match template.graphics_driver {
+        HourDriver::FlatHours => template.graphics_hours,
+        HourDriver::PerUnit => template.graphic_pages as f32 * estimator.hours_per_graphic_page,
+    }
+}
+
+/// Commissioning hours for one equipment built from `template`, honoring its driver.
+fn template_commissioning_hours(template: &EquipmentTemplate, estimator: &EstimatorSettings) -> f32 {
+    match template.commissioning_driver {
+        HourDriver::FlatHours => template.commissioning_hours,
+        HourDriver::PerUnit => {
+            template.commissioning_tests as f32 * estimator.hours_per_commissioning_test
+        }
+    }
+}
+
+fn bind_pdfium() -> anyhow::Result<pdfium_render::prelude::Pdfium> {
+    use anyhow::Context;
+    use pdfium_render::prelude::Pdfium;
+
+    let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .context("pdfium library not available")?;
+    Ok(Pdfium::new(bindings))
+}
+
+fn distance_to_segment(p: egui::Pos2, a: egui::Pos2, b: egui::Pos2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_sq();
+    if len_sq <= f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// Equipment rows against controller columns, marked where `parent_id` assigns them.
+/// Equipment without a controller lands in a trailing "UNASSIGNED" column, and
+/// controllers with nothing under them are flagged in their header.
+fn assignment_matrix_csv(objects: &[BasObject]) -> anyhow::Result<String> {
+    let controllers: Vec<&BasObject> = objects
+        .iter()
+        .filter(|o| o.object_type == ObjectType::Controller)
+        .collect();
+    let mut equipment: Vec<&BasObject> = objects
+        .iter()
+        .filter(|o| o.object_type == ObjectType::Equipment)
+        .collect();
+    equipment.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let mut header = vec!["Equipment".to_string(), "Tag".to_string()];
+    for controller in &controllers {
+        let empty = !equipment.iter().any(|e| e.parent_id == Some(controller.id));
+        header.push(if empty {
+            format!("{} (no equipment)", controller.name)
+        } else {
+            controller.name.clone()
+        });
+    }
+    header.push("UNASSIGNED".to_string());
+    writer.write_record(&header)?;
+
+    for item in equipment {
+        let mut row = vec![item.name.clone(), item.equipment_tag.clone()];
+        let mut assigned = false;
+        for controller in &controllers {
+            let hit = item.parent_id == Some(controller.id);
+            assigned |= hit;
+            row.push(if hit { "X".to_string() } else { String::new() });
+        }
+        row.push(if assigned { String::new() } else { "X".to_string() });
+        writer.write_record(&row)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Point cap implied by a controller license string such as "Edge 25".
+/// Unlimited and Supervisor licenses (and anything without a number) have no cap.
+fn license_point_cap(license: &str) -> Option<usize> {
+    let lower = license.to_lowercase();
+    if lower.contains("unlimited") || lower.contains("supervisor") {
+        return None;
+    }
+    lower
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .last()
+        .and_then(|digits| digits.parse().ok())
+}
+
+fn point_kind_counts(objects: &[BasObject]) -> [(PointKind, usize); 5] {
+    POINT_KINDS.map(|kind| {
+        let count = objects
+            .iter()
+            .filter(|o| o.object_type == ObjectType::Point && o.point_kind == kind)
+            .count();
+        (kind, count)
+    })
+}
+
+/// Zips `project.json` plus assets, obfuscates the archive, and writes it to `path`.
+/// Safe to call off the UI thread.
+fn write_m8_bundle(
+    path: &std::path::Path,
+    payload: &[u8],
+    assets: &[(String, Vec<u8>)],
+) -> Result<(), AppIoError> {
+    use std::io::Write;
+
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    archive.start_file("project.json", options)?;
+    archive.write_all(payload)?;
+    for (name, bytes) in assets {
+        archive.start_file(name.as_str(), options)?;
+        archive.write_all(bytes)?;
+    }
+    let mut bytes = archive.finish()?.into_inner();
+    obfuscate_bytes(&mut bytes);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Reverses `write_m8_bundle`: every archive entry keyed by its name.
+fn read_m8_bundle(path: &std::path::Path) -> Result<HashMap<String, Vec<u8>>, AppIoError> {
+    use std::io::Read;
+
+    let mut bytes = std::fs::read(path)?;
+    deobfuscate_bytes(&mut bytes);
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+    let mut entries = HashMap::new();
+    for idx in 0..archive.len() {
+        let mut file = archive.by_index(idx)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        entries.insert(file.name().to_string(), contents);
+    }
+    Ok(entries)
+}
+
+/// Renders one page at `scale` times its point width, capped at
+/// `OVERLAY_MAX_TEXTURE_SIDE`. Returns the page count, the scale actually used, and the
+/// image; overlay code divides texture size by that scale to stay in page space. Runs
+/// on the overlay render worker.
+fn render_pdf_page(
+    bytes: &[u8],
+    page_index: usize,
+    scale: f32,
+    form_data: bool,
+) -> anyhow::Result<(usize, f32, egui::ColorImage)> {
+    use anyhow::Context;
+
+    let pdfium = bind_pdfium()?;
+    let document = pdfium
+        .load_pdf_from_byte_slice(bytes, None)
+        .context("failed to open drawing PDF")?;
+    let page_count = document.pages().len() as usize;
+    let page = document
+        .pages()
+        .get(page_index as u16)
+        .context("drawing page out of range")?;
+    let (width, height) = (page.width().value, page.height().value);
+    let scale = scale
+        .max(1.0)
+        .min(OVERLAY_MAX_TEXTURE_SIDE / width.max(height).max(1.0));
+    let config = pdfium_render::prelude::PdfRenderConfig::new()
+        .set_target_width((width * scale) as i32)
+        .render_form_data(form_data);
+    let image = page
+        .render_with_config(&config)
+        .context("failed to render drawing page")?
+        .as_image()
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok((page_count, scale, egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw())))
+}
+
+/// Upgrades an older project in place, one version step at a time. Runs in
+/// `load_project_from_path` before `normalize_loaded_project`.
+fn migrate_project(project: &mut Project) {
+    let loaded = project.schema_version;
+    if loaded > PROJECT_SCHEMA_VERSION {
+        tracing::warn!(
+            loaded,
+            supported = PROJECT_SCHEMA_VERSION,
+            "project was saved by a newer AutoMate; unknown fields may be dropped"
+        );
+        return;
+    }
+    for step in &PROJECT_MIGRATIONS[loaded as usize..] {
+        step(project);
+    }
+    if loaded != PROJECT_SCHEMA_VERSION {
+        tracing::info!(from = loaded, to = PROJECT_SCHEMA_VERSION, "migrated project schema");
+    }
+    project.schema_version = PROJECT_SCHEMA_VERSION;
+}
+
+/// v0 → v1: templates stored bare point-name strings; lift them into `TemplatePoint`s.
+fn migrate_v0_template_points(project: &mut Project) {
+    for template in &mut project.templates {
+        if template.points.is_empty() && !template.legacy_points.is_empty() {
+            template.points = template
+                .legacy_points
+                .drain(..)
+                .map(|name| TemplatePoint {
+                    name,
+                    ..Default::default()
+                })
+                .collect();
+        }
+    }
+}
+
+/// v1 → v2: assumptions and exclusions were single text blobs; each becomes a
+/// one-clause list the user can split up.
+fn migrate_v1_proposal_clauses(project: &mut Project) {
+    let proposal = &mut project.proposal;
+    for (legacy, clauses) in [
+        (&mut proposal.legacy_assumptions, &mut proposal.assumptions),
+        (&mut proposal.legacy_exclusions, &mut proposal.exclusions),
+    ] {
+        let text = std::mem::take(legacy);
+        if clauses.is_empty() && !text.trim().is_empty() {
+            clauses.push(text.trim().to_string());
+        }
+    }
+}
+
+/// Directory `autosave_fallback_path` writes unsaved projects into.
+fn autosave_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("com", "AutoMate", "AutoMate")
+        .map(|dirs| dirs.data_local_dir().join("autosave"))
+}
+
+/// Copies a finished autosave to `<uuid>-<timestamp>.m8` in `autosave_dir` and prunes
+/// that project's copies beyond `keep`, oldest first, so one bad edit that autosaves
+/// can't take the last good state with it.
+fn keep_autosave_generation(saved: &std::path::Path, project_uuid: uuid::Uuid, keep: u32) -> std::io::Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+    let Some(dir) = autosave_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let prefix = format!("{project_uuid}-");
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    std::fs::copy(saved, dir.join(format!("{prefix}{stamp}.m8")))?;
+    // The timestamp sorts lexically, so name order is age order.
+    let mut generations: Vec<PathBuf> = std::fs::read_dir(&dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".m8"))
+        })
+        .collect();
+    generations.sort();
+    let excess = generations.len().saturating_sub(keep as usize);
+    for old in &generations[..excess] {
+        std::fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Deletes the fallback autosave and every generation belonging to `project_uuid`.
+fn clear_project_autosaves(project_uuid: uuid::Uuid) -> std::io::Result<()> {
+    let Some(dir) = autosave_dir() else {
+        return Ok(());
+    };
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let prefix = project_uuid.to_string();
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(&prefix) && name.ends_with(".m8") {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn recovery_timestamp(candidate: &RecoveryCandidate) -> String {
+    chrono::DateTime::<chrono::Local>::from(candidate.modified)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Autosaves on disk, newest first. Files are named by `project_uuid`, optionally
+/// followed by a generation timestamp, which lets the recovery prompt tie a file back
+/// to the job it came from. Autosaves no newer than that project's saved file in
+/// `saved_project_paths` hold nothing the file doesn't, so they are left out.
+fn find_recovery_candidates(saved_project_paths: &BTreeMap<uuid::Uuid, PathBuf>) -> Vec<RecoveryCandidate> {
+    let Some(entries) = autosave_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut candidates: Vec<RecoveryCandidate> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m8")))
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            let project_uuid = path.file_stem().and_then(|stem| {
+                let stem = stem.to_string_lossy();
+                // A hyphenated UUID is 36 characters; generations append "-<timestamp>".
+                uuid::Uuid::parse_str(stem.get(..36).unwrap_or(&stem)).ok()
+            });
+            Some(RecoveryCandidate {
+                path,
+                project_uuid,
+                modified,
+            })
+        })
+        .filter(|candidate| {
+            let saved = candidate
+                .project_uuid
+                .and_then(|uuid| saved_project_paths.get(&uuid))
+                .and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok());
+            !saved.is_some_and(|saved| candidate.modified <= saved)
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.modified.cmp(&a.modified));
+    candidates
+}
+
+/// Case-insensitive match of the tree search against an object's name, tag, and
+/// equipment type.
+fn object_matches_query(obj: &BasObject, query: &str) -> bool {
+    let query = query.to_lowercase();
+    [&obj.name, &obj.equipment_tag, &obj.equipment_type]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&query))
+}
+
+/// Maps spreadsheet point-kind text to `PointKind`; unknown values become AI.
+fn parse_point_kind(text: &str) -> PointKind {
+    match text.trim().to_lowercase().as_str() {
+        "di" | "bi" | "digital input" | "binary input" | "binary-input" => PointKind::DI,
+        "ao" | "analog output" | "analog-output" => PointKind::AO,
+        "do" | "bo" | "digital output" | "binary output" | "binary-output" => PointKind::DO,
+        "network" | "net" | "nv" | "bacnet" | "modbus" => PointKind::Network,
+        _ => PointKind::AI,
+    }
+}
+
+fn template_category(template: &EquipmentTemplate) -> &str {
+    let category = template.category.trim();
+    if category.is_empty() {
+        UNCATEGORIZED_TEMPLATES
+    } else {
+        category
+    }
+}
+
+fn legacy_archive_name(name: &str) -> bool {
+    name.to_ascii_lowercase().contains("archive")
+}
+
+fn snap_overlay_point(step: Option<f32>, point: [f32; 2]) -> [f32; 2] {
+    let Some(step) = step else {
+        return point;
+    };
+    point.map(|v| (v / step).round() * step)
+}
+
+fn expenses_markdown(expenses: &[ExpenseLine]) -> String {
+    let mut out = String::from("## Expenses\n\n| Description | Qty | Unit Cost | Amount |\n|---|---:|---:|---:|\n");
+    for line in expenses {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            line.description,
+            line.quantity,
+            format_currency(line.unit_cost),
+            format_currency(line.total())
+        ));
+    }
+    let total: f32 = expenses.iter().map(ExpenseLine::total).sum();
+    out.push_str(&format!("| **Total** | | | **{}** |\n", format_currency(total)));
+    out
+}
+
+fn list_or_defaults(list: &[String], defaults: &[&str]) -> Vec<String> {
+    if list.is_empty() {
+        defaults.iter().map(|s| s.to_string()).collect()
+    } else {
+        list.to_vec()
+    }
+}
+
+/// Combo over `options`; a current value missing from the list is still shown selected.
+fn option_combo(ui: &mut Ui, id: impl std::hash::Hash, value: &mut String, options: &[String]) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id)
+        .selected_text(value.as_str())
+        .show_ui(ui, |ui| {
+            for option in options {
+                changed |= ui.selectable_value(value, option.clone(), option).changed();
+            }
+        });
+    changed
+}
+
+/// Editable list of names for the preferences grid. Editing an empty list starts from
+/// `defaults` so the built-in entries aren't lost.
+fn string_list_editor(ui: &mut Ui, id: &str, list: &mut Vec<String>, defaults: &[&str]) -> bool {
+    let mut changed = false;
+    ui.vertical(|ui| {
+        if list.is_empty() {
+            ui.small(format!("Default: {}", defaults.join(", ")));
+            if ui.small_button("✏ Customize").clicked() {
+                *list = list_or_defaults(&[], defaults);
+                changed = true;
+            }
+            return;
+        }
+        let mut remove = None;
+        for (idx, entry) in list.iter_mut().enumerate() {
+            ui.push_id((id, idx), |ui| {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::TextEdit::singleline(entry).desired_width(160.0)).changed();
+                    if ui.small_button("✖").clicked() {
+                        remove = Some(idx);
+                    }
+                });
+            });
+        }
+        if let Some(idx) = remove {
+            list.remove(idx);
+            changed = true;
+        }
+        if ui.small_button("➕ Add").clicked() {
+            list.push(String::new());
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Editable clause list: each clause can be reordered, removed, or inserted from
+/// `library`. Returns `true` when the list changed.
+fn clause_list_editor(ui: &mut Ui, id: &str, clauses: &mut Vec<String>, library: &[String]) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+    let mut swap = None;
+    let count = clauses.len();
+    for (idx, clause) in clauses.iter_mut().enumerate() {
+        ui.push_id((id, idx), |ui| {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(egui::TextEdit::multiline(clause).desired_rows(1).desired_width(320.0))
+                    .changed();
+                if ui.add_enabled(idx > 0, egui::Button::new("⬆").small()).clicked() {
+                    swap = Some((idx - 1, idx));
+                }
+                if ui.add_enabled(idx + 1 < count, egui::Button::new("⬇").small()).clicked() {
+                    swap = Some((idx, idx + 1));
+                }
+                if ui.small_button("✖").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        });
+    }
+    if let Some((a, b)) = swap {
+        clauses.swap(a, b);
+        changed = true;
+    }
+    if let Some(idx) = remove {
+        clauses.remove(idx);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        if ui.small_button("➕ Add").clicked() {
+            clauses.push(String::new());
+            changed = true;
+        }
+        let available: Vec<&String> = library.iter().filter(|c| !clauses.contains(c)).collect();
+        if !available.is_empty() {
+            ui.menu_button("📚 Insert standard clause", |ui| {
+                for clause in available {
+                    if ui.button(clause.as_str()).clicked() {
+                        clauses.push(clause.clone());
+                        changed = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+    });
+    changed
+}
+
+/// Bulleted proposal section for a clause list; empty when there are no clauses.
+fn clauses_markdown(title: &str, clauses: &[String]) -> String {
+    let mut out = String::new();
+    for clause in clauses.iter().map(|c| c.trim()).filter(|c| !c.is_empty()) {
+        if out.is_empty() {
+            out.push_str(&format!("## {title}\n\n"));
+        }
+        out.push_str(&format!("- {}\n", clause.lines().collect::<Vec<_>>().join("\n  ")));
+    }
+    out
+}
+
+/// Accepts ISO dates plus the US formats estimators tend to type.
+fn parse_proposal_date(value: &str) -> Option<chrono::NaiveDate> {
+    let value = value.trim();
+    ["%Y-%m-%d", "%m/%d/%Y", "%m/%d/%y", "%b %d, %Y", "%B %d, %Y"]
+        .iter()
+        .find_map(|format| chrono::NaiveDate::parse_from_str(value, format).ok())
+}
+
+/// Distinct icon and color for common equipment types; `None` keeps the generic 🛠.
+fn equipment_type_style(equipment_type: &str) -> Option<(&'static str, Color32)> {
+    let lower = equipment_type.to_lowercase();
+    let style = if lower.contains("vav") {
+        ("🌀", Color32::from_rgb(100, 170, 255))
+    } else if lower.contains("ahu") || lower.contains("air handler") {
+        ("🌬", Color32::from_rgb(90, 200, 200))
+    } else if lower.contains("chiller") {
+        ("❄", Color32::from_rgb(140, 210, 255))
+    } else if lower.contains("boiler") {
+        ("🔥", Color32::from_rgb(240, 130, 70))
+    } else if lower.contains("fcu") || lower.contains("fan coil") {
+        ("🔄", Color32::from_rgb(170, 140, 240))
+    } else {
+        return None;
+    };
+    Some(style)
+}
+
+/// Byte range of the first case-insensitive occurrence of `query` in `text`.
+fn match_range(text: &str, query: &str) -> Option<std::ops::Range<usize>> {
+    if query.is_empty() {
+        return None;
+    }
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths outside ASCII; only highlight when offsets agree.
+    if lower.len() != text.len() {
+        return None;
+    }
+    let start = lower.find(&query.to_lowercase())?;
+    let end = start + query.len();
+    (text.is_char_boundary(start) && text.is_char_boundary(end)).then_some(start..end)
+}
+
+/// BACnet object type for a point kind. Network points are integration values with no
+/// physical I/O, so they're exported as values rather than inputs/outputs.
+fn bacnet_object_type(kind: PointKind) -> &'static str {
+    match kind {
+        PointKind::AI => "analog-input",
+        PointKind::DI => "binary-input",
+        PointKind::AO => "analog-output",
+        PointKind::DO => "binary-output",
+        PointKind::Network => "analog-value",
+    }
+}
+
+/// One row per point for BACnet/Niagara object creation. Instances are numbered from 1
+/// per object type within each controller, matching how devices allocate them.
+fn bacnet_points_csv(objects: &[BasObject]) -> anyhow::Result<String> {
+    let find = |id: Option<u64>| id.and_then(|id| objects.iter().find(|o| o.id == id));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "Object Name",
+        "Object Type",
+        "Instance",
+        "Controller",
+        "Equipment",
+        "Path",
+    ])?;
+    let mut instances: HashMap<(Option<u64>, &str), u32> = HashMap::new();
+    for point in objects.iter().filter(|o| o.object_type == ObjectType::Point) {
+        let mut path = vec![point.name.as_str()];
+        let mut equipment = None;
+        let mut controller = None;
+        let mut parent = find(point.parent_id);
+        while let Some(obj) = parent {
+            match obj.object_type {
+                ObjectType::Equipment if equipment.is_none() => equipment = Some(obj),
+                ObjectType::Controller if controller.is_none() => controller = Some(obj),
+                _ => {}
+            }
+            path.push(obj.name.as_str());
+            parent = find(obj.parent_id);
+        }
+        path.reverse();
+        let object_type = bacnet_object_type(point.point_kind);
+        let instance = instances.entry((controller.map(|c| c.id), object_type)).or_insert(0);
+        *instance += 1;
+        writer.write_record([
+            point.name.as_str(),
+            object_type,
+            &instance.to_string(),
+            controller.map_or("", |c| c.name.as_str()),
+            equipment.map_or("", |e| e.name.as_str()),
+            &path.join("/"),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+/// Hardware, license and equipment tallies as `(category, item, quantity)`, sorted by
+/// item within each category. Blank values are counted as "Unspecified".
+fn bill_of_materials(objects: &[BasObject]) -> Vec<(&'static str, String, usize)> {
+    let tally = |kind: ObjectType, field: fn(&BasObject) -> &str| {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for obj in objects.iter().filter(|o| o.object_type == kind) {
+            let value = field(obj).trim();
+            let key = if value.is_empty() { "Unspecified" } else { value };
+            *counts.entry(key.to_string()).or_default() += 1;
+        }
+        counts
+    };
+    let sections = [
+        ("Controller", tally(ObjectType::Controller, |o| o.controller_type.as_str())),
+        ("License", tally(ObjectType::Controller, |o| o.controller_license.as_str())),
+        ("Equipment", tally(ObjectType::Equipment, |o| o.equipment_type.as_str())),
+    ];
+    sections
+        .into_iter()
+        .flat_map(|(category, counts)| counts.into_iter().map(move |(item, qty)| (category, item, qty)))
+        .collect()
+}
+
+fn bom_csv(rows: &[(&'static str, String, usize)]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["Category", "Item", "Quantity"])?;
+    for (category, item, qty) in rows {
+        writer.write_record([*category, item.as_str(), &qty.to_string()])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn bom_markdown(rows: &[(&'static str, String, usize)]) -> String {
+    let mut out = String::from("## Bill of Materials\n\n| Category | Item | Qty |\n|---|---|---:|\n");
+    for (category, item, qty) in rows {
+        out.push_str(&format!("| {category} | {item} | {qty} |\n"));
+    }
+    out
+}
+
+fn drawing_asset_name(idx: usize) -> String {
+    format!("assets/drawings/{idx}.pdf")
+}
+
+/// Single-line input with a dropdown of `catalog` entries containing the typed text.
+/// Returns `(changed, committed)`; committed means a suggestion was picked or the field
+/// lost focus.
+fn autocomplete_field(
+    ui: &mut Ui,
+    id_source: impl std::hash::Hash,
+    value: &mut String,
+    catalog: &BTreeSet<String>,
+) -> (bool, bool) {
+    let response = ui.add(egui::TextEdit::singleline(value).desired_width(180.0));
+    let popup_id = ui.make_persistent_id(id_source);
+    let query = value.trim().to_lowercase();
+    let suggestions: Vec<String> = catalog
+        .iter()
+        .filter(|entry| {
+            !query.is_empty() && entry.to_lowercase().contains(&query) && entry.as_str() != value.trim()
+        })
+        .take(8)
+        .cloned()
+        .collect();
+    if response.has_focus() && !suggestions.is_empty() {
+        ui.memory_mut(|m| m.open_popup(popup_id));
+    }
+    let mut changed = response.changed();
+    let mut picked = false;
+    egui::popup_below_widget(ui, popup_id, &response, |ui| {
+        for suggestion in suggestions {
+            if ui.selectable_label(false, &suggestion).clicked() {
+                *value = suggestion;
+                changed = true;
+                picked = true;
+            }
+        }
+    });
+    (changed, picked || response.lost_focus())
+}
+
+/// "Points Summary" proposal section: totals per `PointKind`, then per equipment type.
+fn points_summary_markdown(objects: &[BasObject]) -> String {
+    let counts = point_kind_counts(objects);
+    let total: usize = counts.iter().map(|(_, n)| n).sum();
+    let mut out = String::from("## Points Summary\n\n| Point Type | Count |\n|---|---:|\n");
+    for (kind, count) in counts {
+        out.push_str(&format!("| {kind:?} | {count} |\n"));
+    }
+    out.push_str(&format!("| **Total** | **{total}** |\n"));
+
+    let mut by_type: BTreeMap<String, [usize; 5]> = BTreeMap::new();
+    for point in objects.iter().filter(|o| o.object_type == ObjectType::Point) {
+        let equipment_type = point
+            .parent_id
+            .and_then(|id| objects.iter().find(|o| o.id == id))
+            .filter(|parent| parent.object_type == ObjectType::Equipment)
+            .map(|parent| parent.equipment_type.trim())
+            .filter(|t| !t.is_empty())
+            .unwrap_or("Unspecified");
+        if let Some(idx) = POINT_KINDS.iter().position(|k| *k == point.point_kind) {
+            by_type.entry(equipment_type.to_string()).or_default()[idx] += 1;
+        }
+    }
+    if by_type.is_empty() {
+        return out;
+    }
+    out.push_str("\n| Equipment Type |");
+    for kind in POINT_KINDS {
+        out.push_str(&format!(" {kind:?} |"));
+    }
+    out.push_str(" Total |\n|---|");
+    out.push_str(&"---:|".repeat(POINT_KINDS.len() + 1));
+    out.push('\n');
+    for (equipment_type, row) in by_type {
+        out.push_str(&format!("| {equipment_type} |"));
+        for count in row {
+            out.push_str(&format!(" {count} |"));
+        }
+        out.push_str(&format!(" {} |\n", row.iter().sum::<usize>()));
+    }
+    out
+}
+
+/// "Clarifications" proposal section: one bullet per object with notes, in tree order
+/// of the project file. Empty when no object has notes.
+fn clarifications_markdown(objects: &[BasObject]) -> String {
+    let mut out = String::new();
+    for obj in objects.iter().filter(|o| !o.notes.trim().is_empty()) {
+        if out.is_empty() {
+            out.push_str("## Clarifications\n\n");
+        }
+        // Continuation lines are indented so multi-line notes stay inside their bullet.
+        let notes = obj.notes.trim().lines().collect::<Vec<_>>().join("\n  ");
+        out.push_str(&format!("- **{}** ({:?}): {notes}\n", obj.name, obj.object_type));
+    }
+    out
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0..=59 => "just now".to_string(),
+        secs @ 60..=3599 => format!("{}m ago", secs / 60),
+        secs => format!("{}h ago", secs / 3600),
+    }
+}
+
+/// One row per overlay wire as "from → to", resolved through the token object ids and
+/// grouped by the controller either end belongs to. Free-floating ends are reported as
+/// "(unconnected)".
+fn wiring_report_csv(
+    objects: &[BasObject],
+    lines: &[OverlayLine],
+    scale: Option<&DrawingScale>,
+) -> anyhow::Result<String> {
+    fn controller_of<'a>(objects: &'a [BasObject], mut current: Option<&'a BasObject>) -> Option<&'a BasObject> {
+        while let Some(o) = current {
+            if o.object_type == ObjectType::Controller {
+                return Some(o);
+            }
+            current = o.parent_id.and_then(|id| objects.iter().find(|p| p.id == id));
+        }
+        None
+    }
+    let find = |id: Option<u64>| id.and_then(|id| objects.iter().find(|o| o.id == id));
+    let mut rows: Vec<(String, String, String, usize, f32)> = lines
+        .iter()
+        .map(|line| {
+            let from = find(line.from_object);
+            let to = find(line.to_object);
+            let controller = controller_of(objects, from)
+                .or_else(|| controller_of(objects, to))
+                .map_or_else(|| "UNASSIGNED".to_string(), |c| c.name.clone());
+            let label = |obj: Option<&BasObject>| obj.map_or_else(|| "(unconnected)".to_string(), |o| o.name.clone());
+            let pixels = egui::pos2(line.from[0], line.from[1]).distance(egui::pos2(line.to[0], line.to[1]));
+            (controller, label(from), label(to), line.page + 1, pixels)
+        })
+        .collect();
+    rows.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let length_header = scale.map_or_else(|| "Length (px)".to_string(), |s| format!("Length ({})", s.unit));
+    writer.write_record(["Controller", "From", "To", "Page", length_header.as_str()])?;
+    for (controller, from, to, page, pixels) in rows {
+        let length = scale.map_or(pixels, |s| pixels * s.units_per_pixel);
+        writer.write_record([controller, from, to, page.to_string(), format!("{length:.1}")])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}